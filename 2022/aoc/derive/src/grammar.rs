@@ -0,0 +1,186 @@
+// #[pest_grammar("src/dayN.pest")] for the pest parsers in aoc.
+//
+// Stands in for pest's #[derive(Parser)] #[grammar = "..."], adding the
+// rules from src/parsing/common.pest that the grammar uses but doesn't
+// define, and the rules those use in turn. pest only reads one grammar
+// per parser, so the result is passed to it inline.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{DeriveInput, Error, LitStr, Result};
+
+const COMMON_GRAMMAR : &str = "src/parsing/common.pest";
+
+struct GrammarRule<'a> {
+    name: &'a str,
+    text: String,
+}
+
+// Splits a grammar into its rules. A rule starts on a line like `name = `
+// and runs until the next one; comments are dropped.
+fn grammar_rules(grammar: &str) -> Vec<GrammarRule<'_>> {
+    let mut rules : Vec<GrammarRule> = Vec::new();
+    for line in grammar.lines() {
+        let code = line.trim();
+        if code.is_empty() || code.starts_with("//") {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((name, _)) if is_ident(name.trim()) => {
+                rules.push(GrammarRule { name: name.trim(), text: line.to_string() });
+            }
+            _ => if let Some(rule) = rules.last_mut() {
+                rule.text.push('\n');
+                rule.text.push_str(line);
+            }
+        }
+    }
+    rules
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// The names a rule's expression refers to, skipping string literals and
+// comments.
+fn references(rule: &GrammarRule) -> Vec<String> {
+    let body = rule.text.split_once('=').map_or("", |(_, body)| body);
+    let mut names = Vec::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                while let Some(d) = chars.next() {
+                    match d {
+                        '\\' => { chars.next(); }
+                        d if d == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for d in chars.by_ref() {
+                    if d == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_alphanumeric() || **d == '_') {
+                    name.push(d);
+                    chars.next();
+                }
+                names.push(name);
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+// pest's built in rules, like ASCII_DIGIT and EOI, are all in capitals.
+fn is_builtin(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+// `grammar` followed by the common rules it needs, or the error if one of
+// those needs a rule (like csv_item) that the grammar has to define.
+fn with_common_rules(grammar: &str, common: &str) -> std::result::Result<String, String> {
+    let own = grammar_rules(grammar);
+    let common = grammar_rules(common);
+    let defined : HashSet<&str> = own.iter().map(|rule| rule.name).collect();
+
+    let mut wanted : Vec<String> = own.iter().flat_map(references).collect();
+    let mut included : HashSet<&str> = HashSet::new();
+    while let Some(name) = wanted.pop() {
+        if defined.contains(name.as_str()) || included.contains(name.as_str()) {
+            continue;
+        }
+        if let Some(rule) = common.iter().find(|rule| rule.name == name) {
+            included.insert(rule.name);
+            wanted.extend(references(rule));
+        }
+    }
+
+    let mut result = grammar.to_string();
+    for rule in common.iter().filter(|rule| included.contains(rule.name)) {
+        let missing = references(rule).into_iter().find(|name| {
+            !is_builtin(name) && !defined.contains(name.as_str()) && !common.iter().any(|r| r.name == name)
+        });
+        if let Some(name) = missing {
+            return Err(format!("the grammar uses {} from {}, so it must define {}", rule.name, COMMON_GRAMMAR, name));
+        }
+        result.push('\n');
+        result.push_str(&rule.text);
+    }
+    Ok(result)
+}
+
+fn read(path: &Path, span: Span) -> Result<String> {
+    fs::read_to_string(path)
+        .map_err(|err| Error::new(span, format!("error reading {}: {}", path.display(), err)))
+}
+
+pub fn expand(path: LitStr, item: DeriveInput) -> Result<TokenStream2> {
+    let root = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".into()));
+    let grammar_path = root.join(path.value());
+    let common_path = root.join(COMMON_GRAMMAR);
+    let grammar = read(&grammar_path, path.span())?;
+    let common = read(&common_path, path.span())?;
+
+    let combined = with_common_rules(&grammar, &common).map_err(|message| Error::new(path.span(), message))?;
+    let grammar_path = grammar_path.display().to_string();
+    let common_path = common_path.display().to_string();
+    Ok(quote! {
+        #[derive(::pest_derive::Parser)]
+        #[grammar_inline = #combined]
+        #item
+
+        // Rebuild when either grammar changes.
+        const _: &str = include_str!(#grammar_path);
+        const _: &str = include_str!(#common_path);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMON : &str = include_str!("../../src/parsing/common.pest");
+
+    fn rule_names(grammar: &str) -> Vec<&str> {
+        grammar_rules(grammar).iter().map(|rule| rule.name).collect()
+    }
+
+    #[test]
+    fn common_rules_come_with_the_rules_they_use() {
+        let grammar = "line = { csv_list ~ eol }\ncsv_item = _{ uint }";
+        let combined = with_common_rules(grammar, COMMON).unwrap();
+        let mut names = rule_names(&combined);
+        names.sort();
+        assert_eq!(names, ["csv_item", "csv_list", "eol", "line", "uint", "ws"]);
+    }
+
+    #[test]
+    fn a_grammar_can_replace_a_common_rule() {
+        let grammar = "int = @{ ASCII_DIGIT+ }\nline = { int ~ ws ~ signed }";
+        let combined = with_common_rules(grammar, COMMON).unwrap();
+        let rules = grammar_rules(&combined);
+        let ints : Vec<&str> = rules.iter().filter(|rule| rule.name == "int").map(|rule| rule.text.as_str()).collect();
+        assert_eq!(ints, ["int = @{ ASCII_DIGIT+ }"]);
+        assert_eq!(rule_names(&combined), ["int", "line", "signed", "ws"]);
+    }
+
+    #[test]
+    fn csv_list_needs_csv_item() {
+        let err = with_common_rules("line = { csv_list ~ eol }", COMMON).unwrap_err();
+        assert_eq!(err, "the grammar uses csv_list from src/parsing/common.pest, so it must define csv_item");
+    }
+}
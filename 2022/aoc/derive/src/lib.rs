@@ -0,0 +1,190 @@
+// #[derive(FromPair)] for the pest-backed input types in aoc.
+//
+// A struct is read from the inner pairs of its rule, one field per pair in
+// declaration order. An enum's rule must have a single inner pair, and the
+// variant is picked by that pair's rule: variant `Expr` matches `Rule::expr`.
+// A variant with one unnamed field converts the inner pair itself; other
+// variants read their fields from its inner pairs like a struct.
+//
+// Attributes, all under #[pair(...)]:
+//   rule_type = "path"  on the type: the pest Rule enum (default `Rule`)
+//   rule = "name"       on a variant: match this rule instead
+//   skip                on a field: don't read a pair, use Default::default()
+//   default = "expr"    on a field: don't read a pair, use `expr`
+//
+// #[pest_grammar] is in grammar.rs.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Fields, GenericParam, Ident, Lifetime, LifetimeDef, Lit, LitStr, Meta, NestedMeta, Path, Result};
+
+mod grammar;
+
+#[proc_macro_derive(FromPair, attributes(pair))]
+pub fn derive_from_pair(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[proc_macro_attribute]
+pub fn pest_grammar(args: TokenStream, item: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(args as LitStr);
+    let item = parse_macro_input!(item as DeriveInput);
+    grammar::expand(path, item).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct PairAttrs {
+    rule_type: Option<Path>,
+    rule: Option<Ident>,
+    skip: bool,
+    default: Option<Expr>,
+}
+
+fn string_value(lit: &Lit) -> Result<String> {
+    match lit {
+        Lit::Str(s) => Ok(s.value()),
+        _ => Err(Error::new_spanned(lit, "expected a string")),
+    }
+}
+
+fn pair_attrs(attrs: &[Attribute]) -> Result<PairAttrs> {
+    let mut result = PairAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("pair")) {
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(Error::new_spanned(attr, "expected #[pair(...)]"));
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => result.skip = true,
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rule_type") => {
+                    result.rule_type = Some(syn::parse_str(&string_value(&nv.lit)?)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rule") => {
+                    result.rule = Some(Ident::new(&string_value(&nv.lit)?, nv.lit.span()));
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("default") => {
+                    result.default = Some(syn::parse_str(&string_value(&nv.lit)?)?);
+                }
+                other => return Err(Error::new_spanned(other, "unknown pair attribute")),
+            }
+        }
+    }
+    Ok(result)
+}
+
+// `Expr` -> `expr`, `ValveName` -> `valve_name`.
+fn rule_name(variant: &Ident) -> Ident {
+    let mut name = String::new();
+    for (i, c) in variant.to_string().chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.extend(c.to_lowercase());
+    }
+    Ident::new(&name, variant.span())
+}
+
+// Builds `fields` from the iterator `pairs`, as the body of a struct or
+// variant constructor.
+fn read_fields(constructor: TokenStream2, fields: &Fields, pairs: &Ident, rule_type: &Path) -> Result<TokenStream2> {
+    let mut values = Vec::new();
+    for field in fields {
+        let attrs = pair_attrs(&field.attrs)?;
+        let ty = &field.ty;
+        values.push(match (attrs.default, attrs.skip) {
+            (Some(default), _) => quote! { #default },
+            (None, true) => quote! { ::std::default::Default::default() },
+            (None, false) => quote! {
+                <_ as ::aoc::parsing::TryParseNext<'__input, #rule_type>>::try_parse_next::<#ty>(&mut #pairs)?
+            },
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => {
+            let names = fields.iter().map(|field| &field.ident);
+            quote! { #constructor { #( #names: #values ),* } }
+        }
+        Fields::Unnamed(_) => quote! { #constructor( #( #values ),* ) },
+        Fields::Unit => constructor,
+    })
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = pair_attrs(&input.attrs)?;
+    let rule_type = attrs.rule_type.unwrap_or_else(|| syn::parse_quote!(Rule));
+    let pair = Ident::new("pair", Span::mixed_site());
+    let pairs = Ident::new("pairs", Span::mixed_site());
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let value = read_fields(quote! { Self }, &data.fields, &pairs, &rule_type)?;
+            quote! {
+                #[allow(unused_mut, unused_variables)]
+                let mut #pairs = ::aoc::parsing::InnerPairs::of(#pair);
+                ::std::result::Result::Ok(#value)
+            }
+        }
+        Data::Enum(data) => {
+            let inner = format_ident!("inner", span = Span::mixed_site());
+            let mut arms = Vec::new();
+            let mut rules = Vec::new();
+            for variant in &data.variants {
+                let attrs = pair_attrs(&variant.attrs)?;
+                let rule = attrs.rule.unwrap_or_else(|| rule_name(&variant.ident));
+                let ident = &variant.ident;
+                let value = match &variant.fields {
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        let ty = &fields.unnamed[0].ty;
+                        quote! {
+                            Self::#ident(<#ty as ::aoc::parsing::TryFromPair<'__input, #rule_type>>::try_from_pair(#inner)?)
+                        }
+                    }
+                    fields => {
+                        let value = read_fields(quote! { Self::#ident }, fields, &pairs, &rule_type)?;
+                        quote! {{
+                            #[allow(unused_mut, unused_variables)]
+                            let mut #pairs = ::aoc::parsing::InnerPairs::of(#inner);
+                            #value
+                        }}
+                    }
+                };
+                arms.push(quote! { #rule_type::#rule => ::std::result::Result::Ok(#value), });
+                rules.push(rule.to_string());
+            }
+            let message = format!("expected one of {}", rules.join(", "));
+            quote! {
+                let #inner = match #pair.clone().into_inner().next() {
+                    ::std::option::Option::Some(#inner) => #inner,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(::aoc::parsing::pair_error(&#pair, "missing pair"));
+                    }
+                };
+                match #inner.as_rule() {
+                    #( #arms )*
+                    _ => ::std::result::Result::Err(::aoc::parsing::pair_error(&#inner, #message)),
+                }
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(&input, "FromPair can't be derived for unions")),
+    };
+
+    // The input outlives anything the type borrows from it.
+    let mut generics = input.generics.clone();
+    let mut input_lifetime = LifetimeDef::new(Lifetime::new("'__input", Span::call_site()));
+    input_lifetime.bounds.extend(input.generics.lifetimes().map(|def| def.lifetime.clone()));
+    generics.params.insert(0, GenericParam::Lifetime(input_lifetime));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::aoc::parsing::TryFromPair<'__input, #rule_type> for #name #ty_generics #where_clause {
+            type Error = ::aoc::error::ParseError;
+
+            fn try_from_pair(#pair: ::pest::iterators::Pair<'__input, #rule_type>) -> ::std::result::Result<Self, Self::Error> {
+                #body
+            }
+        }
+    })
+}
@@ -50,7 +50,7 @@ impl<Node> AStar<Node>
     fn start(&mut self, start: Node, end: &Node) {
         self.scores.insert(start.clone(), 0);
         self.enqueued.insert(start.clone());
-        self.queue.push(WeightedNode::new(start.clone(), start.estimate_cost_to(&end)));
+        self.queue.push(WeightedNode::new(start.clone(), start.estimate_cost_to(end)));
     }
 
     fn score(&self, node: &Node) -> usize {
@@ -60,9 +60,9 @@ impl<Node> AStar<Node>
     fn update_best_path(&mut self, from: &Node, to: &Node, end: &Node, score: usize) {
         self.scores.insert(to.clone(), score);
         self.came_from.insert(to.clone(), from.clone());
-        if !self.enqueued.contains(&to) {
+        if !self.enqueued.contains(to) {
             self.enqueued.insert(to.clone());
-            self.queue.push(WeightedNode::new(to.clone(), score + to.estimate_cost_to(&end)));
+            self.queue.push(WeightedNode::new(to.clone(), score + to.estimate_cost_to(end)));
         }
     }
 
@@ -73,7 +73,7 @@ impl<Node> AStar<Node>
             path.push(to.clone());
         }
         path.reverse();
-        return path;
+        path
     }
 
     fn next(&mut self) -> Option<Node> {
@@ -104,7 +104,7 @@ impl<T: AStarNode> PartialEq for WeightedNode<T> {
 
 impl<T: AStarNode> PartialOrd for WeightedNode<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.weight.partial_cmp(&other.weight)
+        Some(self.cmp(other))
     }
 }
 
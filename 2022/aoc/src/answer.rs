@@ -0,0 +1,45 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i64),
+    Str(String),
+    // Multi-line output that has to be read by eye, like the day 10 CRT.
+    Ascii(Vec<String>),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::Str(s) => f.write_str(s),
+            Answer::Ascii(lines) => f.write_str(&lines.join("\n")),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($( $t:ty ),+) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(i64::try_from(n).expect("answer does not fit in an i64"))
+                }
+            }
+        )+
+    }
+}
+
+answer_from_int!(i32, u32, i64, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Str(s.into())
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use aoc::solution::Registry;
+use aoc::util::input_hash;
+use crate::cli::BenchArgs;
+use crate::report::format_duration;
+use crate::{day_source, read_input, select_days};
+
+// However many runs are asked for, each phase of a part stops after this.
+const BUDGET: Duration = Duration::from_secs(5);
+
+struct Stats {
+    runs: usize,
+    mean: Duration,
+    median: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    fn new(times: &[Duration]) -> Self {
+        let mut sorted = times.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+        let secs : Vec<f64> = sorted.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+        Self {
+            runs: n,
+            mean: Duration::from_secs_f64(mean),
+            median,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+// Keyed by input hash too, so example runs and changed inputs don't get
+// compared against timings for a different input.
+type BaselineKey = (u32, u32, u32, String, u64);
+
+// Median times from an earlier run, one "year day part phase input_hash nanos"
+// per line.
+#[derive(Default)]
+struct Baseline {
+    medians: BTreeMap<BaselineKey, Duration>,
+}
+
+impl Baseline {
+    fn default_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("bench-baseline.txt")
+    }
+
+    fn load(path: &Path) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(format!("Error reading {}: {}", path.display(), err)),
+        };
+        let mut baseline = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let bad_line = || format!("{}:{}: bad baseline entry: {}", path.display(), i + 1, line);
+            let fields : Vec<&str> = line.split_whitespace().collect();
+            let [year, day, part, phase, hash, nanos] = fields.as_slice() else {
+                return Err(bad_line());
+            };
+            let num = |s: &str| s.parse::<u32>().map_err(|_| bad_line());
+            let hash = u64::from_str_radix(hash, 16).map_err(|_| bad_line())?;
+            let nanos : u64 = nanos.parse().map_err(|_| bad_line())?;
+            baseline.medians.insert(
+                (num(year)?, num(day)?, num(part)?, phase.to_string(), hash),
+                Duration::from_nanos(nanos),
+            );
+        }
+        Ok(baseline)
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let mut text = String::from("# year day part phase input_hash median_nanos\n");
+        for ((year, day, part, phase, hash), median) in &self.medians {
+            text += &format!("{} {} {} {} {:016x} {}\n", year, day, part, phase, hash, median.as_nanos());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("Error creating {}: {}", dir.display(), err))?;
+        }
+        fs::write(path, text).map_err(|err| format!("Error writing {}: {}", path.display(), err))
+    }
+}
+
+// Percentage change of `median` against the baseline, and whether it's past
+// the regression threshold.
+fn change(median: Duration, baseline: Duration, threshold: u32) -> (f64, bool) {
+    let percent = (median.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0;
+    (percent, percent > threshold as f64)
+}
+
+pub fn bench(registry: &Registry, args: BenchArgs) -> Result<(), String> {
+    let (year, days) = select_days(registry, args.year, args.days.as_ref())?;
+    let parts = args.part.map(|part| vec![part]).unwrap_or(vec![1, 2]);
+    let path = args.baseline.clone().unwrap_or_else(Baseline::default_path);
+    let mut baseline = Baseline::load(&path)?;
+    let mut regressions = 0;
+
+    println!("{:>3}  {:>4}  {:<5}  {:>4}  {:>9}  {:>9}  {:>9}  {:>9}  Change",
+             "Day", "Part", "Phase", "Runs", "Mean", "Median", "Stddev", "Baseline");
+    for day in days {
+        let solution = registry.get(year, day).unwrap();
+        let source = day_source(day, args.example);
+        let input = read_input(&source)?;
+        let hash = input_hash(&input);
+        for &part in &parts {
+            let times = solution.bench_part(part, &input, args.example, args.runs, BUDGET)
+                .map_err(|err| err.in_file(&source).to_string())?;
+            for (phase, times) in [("parse", &times.parse), ("solve", &times.solve)] {
+                let stats = Stats::new(times);
+                let key = (year, day, part, phase.to_string(), hash);
+                let (base, change) = match baseline.medians.get(&key) {
+                    Some(&base) => {
+                        let (percent, regressed) = change(stats.median, base, args.threshold);
+                        regressions += regressed as usize;
+                        let flag = if regressed { " REGRESSION" } else { "" };
+                        (format_duration(base), format!("{:+.1}%{}", percent, flag))
+                    }
+                    None => ("-".into(), "-".into()),
+                };
+                println!("{:>3}  {:>4}  {:<5}  {:>4}  {:>9}  {:>9}  {:>9}  {:>9}  {}",
+                         day, part, phase, stats.runs,
+                         format_duration(stats.mean), format_duration(stats.median),
+                         format_duration(stats.stddev), base, change);
+                if args.save_baseline {
+                    baseline.medians.insert(key, stats.median);
+                }
+            }
+        }
+    }
+
+    if args.save_baseline {
+        baseline.save(&path)?;
+        println!("Saved baseline to {}", path.display());
+    }
+    if regressions > 0 {
+        return Err(format!("{} regression(s) of more than {}%", regressions, args.threshold));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn close(a: Duration, b: f64) -> bool {
+        (a.as_secs_f64() * 1e3 - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn stats_of_odd_and_even_counts() {
+        let stats = Stats::new(&[ms(3), ms(1), ms(2)]);
+        assert_eq!((stats.runs, stats.median), (3, ms(2)));
+        assert!(close(stats.mean, 2.0) && close(stats.stddev, (2.0f64 / 3.0).sqrt()));
+
+        let stats = Stats::new(&[ms(4), ms(1), ms(3), ms(2)]);
+        assert_eq!((stats.runs, stats.median), (4, Duration::from_micros(2500)));
+        assert!(close(stats.mean, 2.5) && close(stats.stddev, 1.25f64.sqrt()));
+
+        let stats = Stats::new(&[ms(5)]);
+        assert_eq!((stats.runs, stats.median, stats.stddev), (1, ms(5), Duration::ZERO));
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("aoc-bench-{}-{}", process::id(), name))
+    }
+
+    #[test]
+    fn baselines_round_trip() {
+        let dir = scratch("round-trip");
+        let path = dir.join("baseline.txt");
+        assert!(Baseline::load(&path).unwrap().medians.is_empty());
+
+        let mut baseline = Baseline::default();
+        baseline.medians.insert((2022, 16, 1, "parse".into(), 0xabc), Duration::from_nanos(55_123));
+        baseline.medians.insert((2022, 16, 2, "solve".into(), u64::MAX), ms(1500));
+        baseline.save(&path).unwrap();
+        let loaded = Baseline::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.medians, baseline.medians);
+    }
+
+    #[test]
+    fn malformed_baselines_are_errors() {
+        let path = scratch("malformed.txt");
+        for line in ["2022 16 1 parse abc", "2022 16 x parse abc 100", "2022 16 1 parse xyz 100", "2022 16 1 parse abc -1"] {
+            fs::write(&path, format!("# comment\n{}\n", line)).unwrap();
+            let err = Baseline::load(&path).map(|_| ()).unwrap_err();
+            assert_eq!(err, format!("{}:2: bad baseline entry: {}", path.display(), line));
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn changes_past_the_threshold_are_regressions() {
+        let (percent, regressed) = change(ms(105), ms(100), 10);
+        assert!((percent - 5.0).abs() < 1e-9 && !regressed);
+        let (percent, regressed) = change(ms(120), ms(100), 10);
+        assert!((percent - 20.0).abs() < 1e-9 && regressed);
+        let (percent, regressed) = change(ms(50), ms(100), 10);
+        assert!((percent + 50.0).abs() < 1e-9 && !regressed);
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::PathBuf;
+use aoc::util::InputSource;
+use crate::report::Format;
+
+pub const USAGE: &str = "\
+usage: aoc [year] day [1/2] [--input path | -] [--example] [--format table/json/csv] [--jobs n]
+       aoc all [--days 1-25] [--example] [--format table/json/csv] [--jobs n]
+       aoc year --days 1-25 [--example] [--format table/json/csv] [--jobs n]
+       aoc bench [day [1/2] | all [--days 1-25]] [--example] [--runs n]
+                 [--baseline path] [--save-baseline] [--threshold percent]
+       aoc list";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RunArgs {
+    pub year: Option<u32>,
+    pub day: u32,
+    pub part: Option<u32>,
+    pub input: Option<InputSource>,
+    pub example: bool,
+    pub format: Format,
+    pub jobs: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AllArgs {
+    pub year: Option<u32>,
+    pub days: Option<Vec<u32>>,
+    pub example: bool,
+    pub format: Format,
+    pub jobs: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BenchArgs {
+    pub year: Option<u32>,
+    pub days: Option<Vec<u32>>,
+    pub part: Option<u32>,
+    pub example: bool,
+    pub runs: usize,
+    pub baseline: Option<PathBuf>,
+    pub save_baseline: bool,
+    pub threshold: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    List,
+    Run(RunArgs),
+    All(AllArgs),
+    Bench(BenchArgs),
+}
+
+// `years` are the ones with solutions, to tell "2022 16", a year and a day,
+// from "16 2", a day and a part.
+pub fn parse_args<S: AsRef<str>>(args: &[S], years: &[u32]) -> Result<Command, String> {
+    let is_year = |arg: &str| arg.parse().is_ok_and(|year: u32| years.contains(&year));
+    let mut positional: Vec<&str> = Vec::new();
+    let mut input = None;
+    let mut example = false;
+    let mut days = None;
+    let mut format = Format::Table;
+    let mut jobs = 1;
+    let mut runs = None;
+    let mut baseline = None;
+    let mut save_baseline = false;
+    let mut threshold = None;
+    let mut args = args.iter().map(|a| a.as_ref());
+    while let Some(arg) = args.next() {
+        match arg {
+            "--input" | "-i" => {
+                let path = args.next().ok_or("--input needs a path")?;
+                input = Some(input_source(path));
+            }
+            "-" => { input = Some(InputSource::Stdin); }
+            "--example" | "-e" => { example = true; }
+            "--days" | "-d" => {
+                let spec = args.next().ok_or("--days needs a range")?;
+                days = Some(parse_days(spec)?);
+            }
+            "--format" | "-f" => {
+                format = args.next().ok_or("--format needs a format")?.parse()?;
+            }
+            "--jobs" | "-j" => {
+                let n = args.next().ok_or("--jobs needs a number")?;
+                jobs = match n.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("Invalid number of jobs: {}", n)),
+                };
+            }
+            "--runs" | "-n" => {
+                let n = args.next().ok_or("--runs needs a number")?;
+                runs = match n.parse() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => return Err(format!("Invalid number of runs: {}", n)),
+                };
+            }
+            "--baseline" => {
+                baseline = Some(PathBuf::from(args.next().ok_or("--baseline needs a path")?));
+            }
+            "--save-baseline" => { save_baseline = true; }
+            "--threshold" => {
+                threshold = Some(parse_num("threshold", args.next().ok_or("--threshold needs a percentage")?)?);
+            }
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+            arg => positional.push(arg),
+        }
+    }
+
+    if positional.first() == Some(&"bench") {
+        if input.is_some() {
+            return Err("--input doesn't work with bench".into());
+        }
+        let (year, days, part) = match &positional[1..] {
+            [] | ["all"] => (None, days, None),
+            [_, ..] if days.is_some() => return Err("--days only works for a whole year".into()),
+            [day] => (None, Some(vec![parse_num("day", day)?]), None),
+            [year, day] if is_year(year) => (Some(parse_num("year", year)?), Some(vec![parse_num("day", day)?]), None),
+            [day, part] => (None, Some(vec![parse_num("day", day)?]), Some(parse_part(part)?)),
+            [year, day, part] => (
+                Some(parse_num("year", year)?),
+                Some(vec![parse_num("day", day)?]),
+                Some(parse_part(part)?),
+            ),
+            _ => return Err("Invalid args".into()),
+        };
+        return Ok(Command::Bench(BenchArgs {
+            year, days, part, example,
+            runs: runs.unwrap_or(10),
+            baseline,
+            save_baseline,
+            threshold: threshold.unwrap_or(10),
+        }));
+    }
+    if runs.is_some() || baseline.is_some() || save_baseline || threshold.is_some() {
+        return Err("--runs, --baseline, --save-baseline and --threshold only work with bench".into());
+    }
+
+    if days.is_some() || positional == ["all"] {
+        if input.is_some() {
+            return Err("--input only works for a single day".into());
+        }
+        let year = match positional.as_slice() {
+            [] | ["all"] => None,
+            [year] => Some(parse_num("year", year)?),
+            _ => return Err("--days only works for a whole year".into()),
+        };
+        return Ok(Command::All(AllArgs { year, days, example, format, jobs }));
+    }
+
+    match positional.as_slice() {
+        ["list"] => Ok(Command::List),
+        [day] => Ok(Command::Run(RunArgs {
+            year: None,
+            day: parse_num("day", day)?,
+            part: None,
+            input,
+            example,
+            format,
+            jobs,
+        })),
+        [year, day] if is_year(year) => Ok(Command::Run(RunArgs {
+            year: Some(parse_num("year", year)?),
+            day: parse_num("day", day)?,
+            part: None,
+            input,
+            example,
+            format,
+            jobs,
+        })),
+        [day, part] => Ok(Command::Run(RunArgs {
+            year: None,
+            day: parse_num("day", day)?,
+            part: Some(parse_part(part)?),
+            input,
+            example,
+            format,
+            jobs,
+        })),
+        [year, day, part] => Ok(Command::Run(RunArgs {
+            year: Some(parse_num("year", year)?),
+            day: parse_num("day", day)?,
+            part: Some(parse_part(part)?),
+            input,
+            example,
+            format,
+            jobs,
+        })),
+        _ => Err("Invalid args".into()),
+    }
+}
+
+fn input_source(path: &str) -> InputSource {
+    if path == "-" {
+        InputSource::Stdin
+    } else {
+        InputSource::File(PathBuf::from(path))
+    }
+}
+
+// Accepts "10-21", "5" or a comma separated mix like "1,3,10-12".
+fn parse_days(spec: &str) -> Result<Vec<u32>, String> {
+    let mut days = Vec::new();
+    for range in spec.split(',') {
+        match range.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse_num("day", first)?, parse_num("day", last)?);
+                if first > last {
+                    return Err(format!("Invalid day range: {}", range));
+                }
+                days.extend(first..=last);
+            }
+            None => days.push(parse_num("day", range)?),
+        }
+    }
+    Ok(days)
+}
+
+fn parse_part(arg: &str) -> Result<u32, String> {
+    match parse_num("part", arg)? {
+        part @ (1 | 2) => Ok(part),
+        _ => Err(format!("Invalid part: {}", arg)),
+    }
+}
+
+fn parse_num(name: &str, arg: &str) -> Result<u32, String> {
+    arg.parse().map_err(|_| format!("Invalid {}: {}", name, arg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        let args : Vec<&str> = args.split_whitespace().collect();
+        parse_args(&args, &[2022])
+    }
+
+    fn run_args(day: u32, part: Option<u32>) -> RunArgs {
+        RunArgs { year: None, day, part, input: None, example: false, format: Format::Table, jobs: 1 }
+    }
+
+    fn run(year: Option<u32>, day: u32, part: Option<u32>) -> Command {
+        Command::Run(RunArgs { year, ..run_args(day, part) })
+    }
+
+    #[test]
+    fn two_numbers_are_a_year_and_day_if_the_first_is_a_year() {
+        assert_eq!(parse("2022 16"), Ok(run(Some(2022), 16, None)));
+        assert_eq!(parse("16 2"), Ok(run(None, 16, Some(2))));
+        assert_eq!(parse("2022 16 2"), Ok(run(Some(2022), 16, Some(2))));
+        assert_eq!(parse("16"), Ok(run(None, 16, None)));
+        assert_eq!(parse("16 3"), Err("Invalid part: 3".into()));
+        // Only years with solutions count.
+        assert_eq!(parse("2021 16"), Err("Invalid part: 16".into()));
+    }
+
+    #[test]
+    fn bench_reads_two_numbers_the_same_way() {
+        let Ok(Command::Bench(args)) = parse("bench 2022 16") else { panic!("not a bench") };
+        assert_eq!((args.year, args.days, args.part), (Some(2022), Some(vec![16]), None));
+        let Ok(Command::Bench(args)) = parse("bench 16 2") else { panic!("not a bench") };
+        assert_eq!((args.year, args.days, args.part), (None, Some(vec![16]), Some(2)));
+    }
+
+    #[test]
+    fn input_comes_from_a_path_or_stdin() {
+        let with_input = |input| Command::Run(RunArgs { input: Some(input), ..run_args(16, Some(2)) });
+        assert_eq!(parse("16 2 --input in.txt"), Ok(with_input(InputSource::File("in.txt".into()))));
+        assert_eq!(parse("-i in.txt 16 2"), Ok(with_input(InputSource::File("in.txt".into()))));
+        assert_eq!(parse("16 2 --input -"), Ok(with_input(InputSource::Stdin)));
+        assert_eq!(parse("16 2 -"), Ok(with_input(InputSource::Stdin)));
+        assert_eq!(parse("16 --input"), Err("--input needs a path".into()));
+
+        assert_eq!(parse("all -"), Err("--input only works for a single day".into()));
+        assert_eq!(parse("--days 1-3 --input in.txt"), Err("--input only works for a single day".into()));
+        assert_eq!(parse("bench 16 -"), Err("--input doesn't work with bench".into()));
+    }
+
+    #[test]
+    fn example_picks_the_example_input() {
+        let example = Command::Run(RunArgs { example: true, ..run_args(16, None) });
+        assert_eq!(parse("16 --example"), Ok(example));
+        assert!(matches!(parse("all -e"), Ok(Command::All(AllArgs { example: true, .. }))));
+        assert!(matches!(parse("bench 16 -e"), Ok(Command::Bench(BenchArgs { example: true, .. }))));
+    }
+
+    fn all(year: Option<u32>, days: Option<Vec<u32>>) -> Command {
+        Command::All(AllArgs { year, days, example: false, format: Format::Table, jobs: 1 })
+    }
+
+    #[test]
+    fn all_runs_a_year_or_some_of_its_days() {
+        assert_eq!(parse("all"), Ok(all(None, None)));
+        assert_eq!(parse("all --days 3"), Ok(all(None, Some(vec![3]))));
+        assert_eq!(parse("2022 --days 10-12"), Ok(all(Some(2022), Some(vec![10, 11, 12]))));
+        assert_eq!(parse("-d 1,3,10-12"), Ok(all(None, Some(vec![1, 3, 10, 11, 12]))));
+        assert!(matches!(parse("all -f csv"), Ok(Command::All(AllArgs { format: Format::Csv, .. }))));
+
+        assert_eq!(parse("2022 16 --days 1-3"), Err("--days only works for a whole year".into()));
+        assert_eq!(parse("bench 16 --days 1-3"), Err("--days only works for a whole year".into()));
+        assert_eq!(parse("all --days"), Err("--days needs a range".into()));
+        assert_eq!(parse("all --days 5-3"), Err("Invalid day range: 5-3".into()));
+        assert_eq!(parse("all --days 1-x"), Err("Invalid day: x".into()));
+        assert_eq!(parse("all --format xml"), Err("Unknown format: xml".into()));
+    }
+
+    #[test]
+    fn jobs_must_be_positive() {
+        assert!(matches!(parse("all --jobs 4"), Ok(Command::All(AllArgs { jobs: 4, .. }))));
+        assert!(matches!(parse("16 -j 2"), Ok(Command::Run(RunArgs { jobs: 2, .. }))));
+        assert_eq!(parse("all --jobs 0"), Err("Invalid number of jobs: 0".into()));
+        assert_eq!(parse("all --jobs many"), Err("Invalid number of jobs: many".into()));
+        assert_eq!(parse("all --jobs"), Err("--jobs needs a number".into()));
+    }
+}
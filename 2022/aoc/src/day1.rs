@@ -1,19 +1,36 @@
-use crate::util::read_lines;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::parsing::lines::line_groups;
+use crate::solution::Solution;
 
-fn read_calories(filename: &str) -> Vec<i32> {
-    let lines = read_lines(filename);
-    lines
-        .split(|line| line == "")
-        .map(|c| c.iter().map(|line| line.parse::<i32>().unwrap() ).sum())
+fn read_calories(input: &str) -> Result<Vec<i32>, ParseError> {
+    line_groups(input).iter()
+        .map(|group| group.iter().map(|line| line.parse::<i32>()).sum())
         .collect()
 }
 
-pub fn part1() {
-    println!("{}",  read_calories("inputs/day1/part1.txt").iter().max().unwrap());
-}
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input<'a> = Vec<i32>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i32>> {
+        Ok(read_calories(input)?)
+    }
 
-pub fn part2() {
-    let mut calories = read_calories("inputs/day1/part2.txt");
-    calories.sort();
-    println!("{}", calories.iter().rev().take(3).sum::<i32>());
+    fn part1(&self, calories: &Vec<i32>) -> Result<Answer> {
+        let max = calories.iter().max().ok_or(Error::no_solution("no elves"))?;
+        Ok((*max).into())
+    }
+
+    fn part2(&self, calories: &Vec<i32>) -> Result<Answer> {
+        let mut calories = calories.clone();
+        calories.sort();
+        Ok(calories.iter().rev().take(3).sum::<i32>().into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [24000.into(), 45000.into()]
+    }
 }
+
@@ -1,7 +1,10 @@
-use crate::util::read_lines;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::parsing::lines::lines;
+use crate::solution::Solution;
 
 #[derive(Copy, Clone, Debug)]
-enum Instruction {
+pub enum Instruction {
     Addx(i32),
     Noop
 }
@@ -52,7 +55,6 @@ impl Cpu {
             }
             Noop => { self.next_inst(); }
         }
-        return;
     }
 
     fn next_inst(&mut self) {
@@ -61,37 +63,60 @@ impl Cpu {
     }
 }
 
-fn parse_input() -> Vec<Instruction> {
-   read_lines("inputs/day10/input.txt").iter().map(|line| {
-       let mut parts = line.split(" ");
-       match parts.next().unwrap() {
-           "addx" => Addx(parts.next().unwrap().parse().unwrap()),
-           "noop" => Noop,
-           _ => { unreachable!() }
+fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+   lines(input).map(|line| {
+       match line.text.split_once(' ') {
+           Some(("addx", value)) => Ok(Addx(line.parse_token(value)?)),
+           None if line.text == "noop" => Ok(Noop),
+           _ => Err(line.error(1, format!("unknown instruction {:?}", line.text))),
        }
    }).collect()
 }
 
-pub fn part1() {
-    let mut cpu = Cpu::new(parse_input());
-    let total_signal_strength : i32= (0..220)
-        .map(|_| {
-            cpu.tick();
-            (cpu.cycle, cpu.signal_strength())
-        })
-        .filter(|(cycle,_)| (cycle + 20) % 40 == 0 )
-        .map(|(_,signal_strength)| signal_strength )
-        .sum();
-    println!("{}", total_signal_strength);
-}
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input<'a> = Vec<Instruction>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Instruction>> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        let mut cpu = Cpu::new(instructions.clone());
+        let total_signal_strength : i32= (0..220)
+            .map(|_| {
+                cpu.tick();
+                (cpu.cycle, cpu.signal_strength())
+            })
+            .filter(|(cycle,_)| (cycle + 20) % 40 == 0 )
+            .map(|(_,signal_strength)| signal_strength )
+            .sum();
+        Ok(total_signal_strength.into())
+    }
+
+    fn part2(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        let mut cpu = Cpu::new(instructions.clone());
+        let chars : Vec<_> = (0..240)
+            .map(|_| {
+                let char = cpu.crt_char();
+                cpu.tick();
+                char
+            }).collect();
+        Ok(Answer::Ascii(chars.chunks(40).map(|line| line.iter().collect()).collect()))
+    }
 
-pub fn part2() {
-    let mut cpu = Cpu::new(parse_input());
-    let chars : Vec<_> = (0..240)
-        .map(|_| {
-            let char = cpu.crt_char();
-            cpu.tick();
-            char
-        }).collect();
-    chars.chunks(40).for_each(|line| println!("{}", line.iter().collect::<String>()));
-}
\ No newline at end of file
+    fn example_answers(&self) -> [Answer; 2] {
+        [
+            13140.into(),
+            Answer::Ascii(vec![
+                "XX..XX..XX..XX..XX..XX..XX..XX..XX..XX..".into(),
+                "XXX...XXX...XXX...XXX...XXX...XXX...XXX.".into(),
+                "XXXX....XXXX....XXXX....XXXX....XXXX....".into(),
+                "XXXXX.....XXXXX.....XXXXX.....XXXXX.....".into(),
+                "XXXXXX......XXXXXX......XXXXXX......XXXX".into(),
+                "XXXXXXX.......XXXXXXX.......XXXXXXX.....".into(),
+            ]),
+        ]
+    }
+}
@@ -1,12 +1,15 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::mem::swap;
 use std::ops::{Add, Div, Mul, Rem};
 use pest::iterators::Pair;
-use crate::parsing::{FromPair, ParseFile, ParseNext};
+use crate::parsing::{pair_error, pest_grammar, FromPair, InnerPairs, ParseFile, TryFromPair, TryParseNext};
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar = "src/day11.pest"]
+#[pest_grammar("src/day11.pest")]
 struct InputParser;
 
 trait Square {
@@ -78,31 +81,35 @@ enum Operand {
 }
 use Operand::*;
 
-impl FromPair<Rule> for Operand {
-    fn from_pair(pair: Pair<Rule>) -> Self {
+impl TryFromPair<'_, Rule> for Operand {
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<Rule>) -> Result<Self, ParseError> {
         match pair.as_str() {
-            "old" => Old,
-            num => Num(num.parse().unwrap()),
+            "old" => Ok(Old),
+            _ => Ok(Num(i32::try_from_pair(pair)?)),
         }
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 enum Op { Add, Mul }
-impl FromPair<Rule> for Op {
-    fn from_pair(pair: Pair<Rule>) -> Self {
+impl TryFromPair<'_, Rule> for Op {
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<Rule>) -> Result<Self, ParseError> {
         match pair.as_str() {
-            "+" => Op::Add,
-            "*" => Op::Mul,
-            _ => unreachable!()
+            "+" => Ok(Op::Add),
+            "*" => Ok(Op::Mul),
+            op => Err(pair_error(&pair, format!("unknown operation {:?}", op))),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, FromPair)]
 struct Operation {
-    rhs: Operand,
     op: Op,
+    rhs: Operand,
 }
 
 impl Operation {
@@ -111,18 +118,8 @@ impl Operation {
         match (self.op, self.rhs) {
             (Op::Add, Num(v)) => old + v,
             (Op::Mul, Num(v)) => old * v,
+            (Op::Add, Old) => old * 2,
             (Op::Mul, Old) => old.square(),
-            _ => unreachable!()
-        }
-    }
-}
-
-impl FromPair<Rule> for Operation {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        Self {
-            op: pairs.parse_next(),
-            rhs: pairs.parse_next(),
         }
     }
 }
@@ -134,13 +131,15 @@ struct ThrowDecision {
     if_not_divisible_monkey: usize,
 }
 
-impl FromPair<Rule> for ThrowDecision {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        let (divisor,) = pairs.parse_next();
-        let (if_divisible_monkey,) = pairs.parse_next();
-        let (if_not_divisible_monkey,) = pairs.parse_next();
-        ThrowDecision { divisor, if_divisible_monkey, if_not_divisible_monkey }
+impl TryFromPair<'_, Rule> for ThrowDecision {
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<Rule>) -> Result<Self, ParseError> {
+        let mut pairs = InnerPairs::of(pair);
+        let (divisor,) = pairs.try_parse_next()?;
+        let (if_divisible_monkey,) = pairs.try_parse_next()?;
+        let (if_not_divisible_monkey,) = pairs.try_parse_next()?;
+        Ok(ThrowDecision { divisor, if_divisible_monkey, if_not_divisible_monkey })
     }
 }
 
@@ -156,24 +155,13 @@ impl ThrowDecision {
     }
 }
 
-#[derive(Clone, Debug)]
-struct MonkeyInput {
+#[derive(Clone, Debug, FromPair)]
+pub struct MonkeyInput {
     items: Vec<i32>,
     operation: Operation,
     throw_decision: ThrowDecision,
 }
 
-impl FromPair<Rule> for MonkeyInput {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs= pair.into_inner();
-        MonkeyInput {
-            items: pairs.parse_next(),
-            operation: pairs.parse_next(),
-            throw_decision: pairs.parse_next(),
-        }
-    }
-}
-
 #[derive(Clone, Debug)]
 struct RemMap(HashMap<i32,i32>);
 
@@ -231,9 +219,9 @@ struct Monkey<ItemType: Item> {
 }
 
 
-fn parse_input() -> Vec<MonkeyInput> {
-    let (input,) : (Vec<MonkeyInput>,) = InputParser::parse_file(Rule::input, "inputs/day11/input.txt");
-    return input;
+fn parse_input(input: &str) -> Result<Vec<MonkeyInput>> {
+    let (input,) : (Vec<MonkeyInput>,) = InputParser::parse_str(Rule::input, input)?;
+    Ok(input)
 }
 
 
@@ -244,7 +232,7 @@ struct Throw<T> {
 }
 
 impl<ItemType: Item> Monkey<ItemType> {
-    fn new(input: MonkeyInput, divisors: &Vec<i32>) -> Self {
+    fn new(input: MonkeyInput, divisors: &[i32]) -> Self {
         Self{
             total_inspected: 0,
             items: input.items.iter().map(|item| ItemType::from_initial(*item, divisors.iter().copied())).collect(),
@@ -269,9 +257,8 @@ impl<ItemType: Item> Monkey<ItemType> {
     fn throw_items(&mut self) -> Vec<Throw<ItemType>> {
         let mut items : Vec<ItemType> = Vec::new();
         swap(&mut items, &mut self.items);
-        let throws = items.into_iter()
-            .map(|item| Throw { monkey_idx: self.throw_decision.throw_to(&item), item }).collect();
-        throws
+        items.into_iter()
+            .map(|item| Throw { monkey_idx: self.throw_decision.throw_to(&item), item }).collect()
     }
 }
 
@@ -305,23 +292,37 @@ impl<T: Item> MonkeyList<T> {
     }
 
     fn score(mut self) -> usize {
-        self.0.sort_by(|a, b| b.total_inspected.cmp(&a.total_inspected) );
+        self.0.sort_by_key(|m| Reverse(m.total_inspected));
         self.0[0].total_inspected * self.0[1].total_inspected
     }
 }
 
-pub fn part1() {
-    let mut monkeys = MonkeyList::<IntItem>::new(parse_input());
-    for _ in 0..20 {
-        monkeys.process_part1_round();
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input<'a> = Vec<MonkeyInput>;
+
+    fn parse(&self, input: &str) -> Result<Vec<MonkeyInput>> {
+        parse_input(input)
+    }
+
+    fn part1(&self, input: &Vec<MonkeyInput>) -> Result<Answer> {
+        let mut monkeys = MonkeyList::<IntItem>::new(input.clone());
+        for _ in 0..20 {
+            monkeys.process_part1_round();
+        }
+        Ok(monkeys.score().into())
+    }
+
+    fn part2(&self, input: &Vec<MonkeyInput>) -> Result<Answer> {
+        let mut monkeys = MonkeyList::<RemMap>::new(input.clone());
+        for _ in 0..10000 {
+            monkeys.process_part2_round();
+        }
+        Ok(monkeys.score().into())
     }
-    println!("{:?}", monkeys.score());
-}
 
-pub fn part2() {
-    let mut monkeys = MonkeyList::<RemMap>::new(parse_input());
-    for _ in 0..10000 {
-        monkeys.process_part2_round();
+    fn example_answers(&self) -> [Answer; 2] {
+        [10605.into(), 2713310158i64.into()]
     }
-    println!("{:?}", monkeys.score());
-}
\ No newline at end of file
+}
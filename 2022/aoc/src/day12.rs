@@ -1,67 +1,51 @@
 use std::fmt::Debug;
-use std::fs::read_to_string;
 use std::str::FromStr;
 use crate::a_star::{a_star, AStarNode};
 use crate::grid::*;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::solution::{InputValidator, Solution};
 
 type ElevationGrid = VecGrid<i32>;
 
-struct Input {
+pub struct Input {
     grid: ElevationGrid,
-    start: (i32, i32),
-    end: (i32, i32),
+    // Every square marked S or E; validate wants one of each.
+    starts: Vec<(i32, i32)>,
+    ends: Vec<(i32, i32)>,
 }
 
-impl<'a> FromStr for Input {
-    type Err = String;
+impl FromStr for Input {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-          static ref CHARS : Vec<char> = ('a'..='z').collect();
-        }
-        let mut start : Option<(i32, i32)> = None;
-        let mut end : Option<(i32, i32)> = None;
-        let grid : ElevationGrid = s.lines().enumerate().map(|(y,line)| {
-            line.chars().enumerate().map(|(x, char)| {
-               match char {
-                   'S' => { start = Some((y as i32, x as i32)); Ok(0) }
-                   'E' => { end = Some((y as i32, x as i32)); Ok(25) }
-                   'a'..='z' => { Ok(CHARS.binary_search(&char).unwrap() as i32) }
-                   _ => Err(format!("invalid char: {}", char))
-               }
-            }).collect::<Result<Vec<i32>, String>>()
-        }).collect::<Result<Vec<Vec<i32>>, String>>()?.into();
-        if let (Some(start), Some(end)) = (start, end) {
-            Ok(Input { start, end, grid })
-        } else {
-            Err(format!("Invalid start: {:?} or end: {:?}", start, end))
-        }
+        let (grid, mut markers) = ElevationGrid::parse_chars_with_markers(s, &['S', 'E'], |c| match c {
+            'S' => Ok(0),
+            'E' => Ok(25),
+            'a'..='z' => Ok(c as i32 - 'a' as i32),
+            _ => Err(format!("invalid char: {:?}", c)),
+        })?;
+        Ok(Input {
+            starts: markers.remove(&'S').unwrap_or_default(),
+            ends: markers.remove(&'E').unwrap_or_default(),
+            grid,
+        })
     }
 }
 
-fn read_input() -> Input {
-    read_to_string("inputs/day12/input.txt").unwrap().parse().unwrap()
+fn read_input(input: &str) -> Result<Input, ParseError> {
+    input.parse()
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Pos<'a>(GridCursor<'a, ElevationGrid>);
 
-fn push_neigh_if_valid<'a, 'b: 'a>(vec: &'a mut Vec<Pos<'b>>, cur: &GridCursor<'b, ElevationGrid>, maybe_neigh: Option<GridCursor<'b, ElevationGrid>>) {
-    if let Some(neigh) = maybe_neigh {
-        if *neigh <= **cur + 1 {
-            vec.push(Pos(neigh));
-        }
-    }
-}
-
 impl<'a> AStarNode for Pos<'a> {
     fn neighbors(&self) -> Vec<Self> {
-        let mut vec = Vec::new();
-        push_neigh_if_valid(&mut vec, &self.0, self.0.left());
-        push_neigh_if_valid(&mut vec, &self.0, self.0.right());
-        push_neigh_if_valid(&mut vec, &self.0, self.0.up());
-        push_neigh_if_valid(&mut vec, &self.0, self.0.down());
-        vec
+        self.0.neighbors4()
+            .filter(|neigh| **neigh <= *self.0 + 1)
+            .map(Pos)
+            .collect()
     }
 
     fn estimate_cost_to(&self, other: &Self) -> usize {
@@ -70,24 +54,42 @@ impl<'a> AStarNode for Pos<'a> {
 }
 
 
-pub fn part1() {
-    let Input { grid, start, end } = read_input();
-    let start_pos = Pos(grid.cursor_at(start.0, start.1).unwrap());
-    let end_pos = Pos(grid.cursor_at(end.0, end.1).unwrap());
-    let path = a_star(start_pos, end_pos).unwrap();
-    println!("{}", path.len() - 1);
-}
+pub struct Day12;
 
-pub fn part2() {
-    let Input { grid, start: _,  end } = read_input();
-    let end_pos = Pos(grid.cursor_at(end.0, end.1).unwrap());
-    let min = grid.cursors()
-        .filter(|c| **c == 0)
-        .map(|start| {
-            a_star(Pos(start), end_pos.clone())
-                .map(|path| path.len() - 1)
-                .unwrap_or(usize::MAX)
-        })
-        .min().unwrap();
-    println!("{}", min);
-}
\ No newline at end of file
+impl Solution for Day12 {
+    type Input<'a> = Input;
+
+    fn parse(&self, input: &str) -> Result<Input> {
+        Ok(read_input(input)?)
+    }
+
+    fn validate(&self, input: &Input, validator: &mut InputValidator) {
+        let places = |squares: &[(i32, i32)]| -> Vec<String> {
+            squares.iter().map(|(y, x)| format!("line {} column {}", y + 1, x + 1)).collect()
+        };
+        validator.exactly_one("start square 'S'", &places(&input.starts));
+        validator.exactly_one("end square 'E'", &places(&input.ends));
+    }
+
+    fn part1(&self, Input { grid, starts, ends }: &Input) -> Result<Answer> {
+        let start_pos = Pos(grid.cursor_at(starts[0].0, starts[0].1).unwrap());
+        let end_pos = Pos(grid.cursor_at(ends[0].0, ends[0].1).unwrap());
+        let path = a_star(start_pos, end_pos).ok_or(Error::no_solution("there is no path from S to E"))?;
+        Ok((path.len() - 1).into())
+    }
+
+    fn part2(&self, Input { grid, starts: _, ends }: &Input) -> Result<Answer> {
+        let end_pos = Pos(grid.cursor_at(ends[0].0, ends[0].1).unwrap());
+        let min = grid.cursors()
+            .filter(|c| **c == 0)
+            .filter_map(|start| {
+                a_star(Pos(start), end_pos.clone()).map(|path| path.len() - 1)
+            })
+            .min().ok_or(Error::no_solution("there is no path from an 'a' square to E"))?;
+        Ok(min.into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [31.into(), 29.into()]
+    }
+}
@@ -1,15 +1,17 @@
 use std::cmp::Ordering;
 use std::rc::Rc;
-use pest::iterators::Pair;
-use crate::parsing::{FromPair, ParseFile, ParseInto, ParseNext};
+use crate::parsing::{pest_grammar, FromPair, ParseFile};
+use crate::answer::Answer;
+use crate::error::Result;
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar = "src/day13.pest"]
+#[pest_grammar("src/day13.pest")]
 struct InputParser;
 
 type List = Vec<Elem>;
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, FromPair)]
 enum Elem {
+    #[pair(rule = "int")]
     Num(i32),
     List(Rc<List>),
 }
@@ -38,43 +40,17 @@ impl Ord for Elem {
     }
 }
 
-impl FromPair<Rule> for Elem {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let inner_pair = pair.into_inner().next().unwrap();
-
-        match inner_pair.as_rule() {
-            Rule::num => Elem::Num(inner_pair.parse_into()),
-            Rule::list => Elem::List(inner_pair.parse_into()),
-            _ => panic!("Unexpected elem: {}", inner_pair)
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, FromPair)]
 struct Packet {
   list: List
 }
 
-impl FromPair<Rule> for Packet {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        Self { list: pairs.parse_next() }
-    }
-}
-
-#[derive(Debug)]
-struct Input {
+#[derive(Debug, FromPair)]
+pub struct Input {
   packet_pairs: Vec<(Packet, Packet)>
 }
-impl FromPair<Rule> for Input {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        Self { packet_pairs: pairs.parse_next() }
-    }
-}
-
-fn parse_input() -> Input {
-    InputParser::parse_file(Rule::input, "inputs/day13/input.txt")
+fn parse_input(input: &str) -> Result<Input> {
+    Ok(InputParser::parse_str(Rule::input, input)?)
 }
 
 fn divider_packet(n: i32) -> Packet {
@@ -83,23 +59,35 @@ fn divider_packet(n: i32) -> Packet {
     )]}
 }
 
-pub fn part1() {
-    let Input { packet_pairs } = parse_input();
-    let sum : usize = packet_pairs.iter().enumerate().filter(|(_,(p1, p2))| {
-        p1 <= p2
-    }).map(|(i,_)| i + 1).sum();
-    println!("{:?}", sum);
-}
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input<'a> = Input;
+
+    fn parse(&self, input: &str) -> Result<Input> {
+        parse_input(input)
+    }
+
+    fn part1(&self, Input { packet_pairs }: &Input) -> Result<Answer> {
+        let sum : usize = packet_pairs.iter().enumerate().filter(|(_,(p1, p2))| {
+            p1 <= p2
+        }).map(|(i,_)| i + 1).sum();
+        Ok(sum.into())
+    }
+
+    fn part2(&self, Input { packet_pairs }: &Input) -> Result<Answer> {
+        let mut packets : Vec<_> = packet_pairs.iter().flat_map(|(a,b)| [a.clone(),b.clone()]).collect();
+        let div1 = divider_packet(2);
+        let div2 = divider_packet(6);
+        packets.push(div1.clone());
+        packets.push(div2.clone());
+        packets.sort();
+        let score = (packets.binary_search(&div1).unwrap() + 1) *
+            (packets.binary_search(&div2).unwrap() + 1);
+        Ok(score.into())
+    }
 
-pub fn part2() {
-    let Input { packet_pairs } = parse_input();
-    let mut packets : Vec<_> = packet_pairs.into_iter().map(|(a,b)| [a,b]).flatten().collect();
-    let div1 = divider_packet(2);
-    let div2 = divider_packet(6);
-    packets.push(div1.clone());
-    packets.push(div2.clone());
-    packets.sort();
-    let score = (packets.binary_search(&div1).unwrap() + 1) *
-        (packets.binary_search(&div2).unwrap() + 1);
-    println!("{}", score);
-}
\ No newline at end of file
+    fn example_answers(&self) -> [Answer; 2] {
+        [13.into(), 140.into()]
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::{Display, Formatter, Write};
-use crate::grid::{Grid, HashMapGrid};
-use crate::parsing::ParseFile;
+use crate::grid::{Grid, GridCursorMut, GridMut, HashMapGrid, Offset};
+use crate::parsing::{pest_grammar, ParseFile};
+use crate::answer::Answer;
+use crate::error::Result;
+use crate::solution::{InputValidator, Solution};
 
-#[derive(Parser)]
-#[grammar = "src/day14.pest"]
+#[pest_grammar("src/day14.pest")]
 struct InputParser;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -25,6 +27,10 @@ impl Display for Tile {
 
 type Cave = HashMapGrid<Tile>;
 
+const SOURCE : (i32, i32) = (0, 500);
+// In the order sand tries them.
+const FALLS : [Offset; 3] = [Offset::Down, Offset::DownLeft, Offset::DownRight];
+
 type Point = (i32, i32);
 type Line = Vec<Point>;
 struct LineSegIterator {
@@ -41,7 +47,7 @@ impl Iterator for LineSegIterator {
     type Item = Point ;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.cur.map(|cur| {
+        self.cur.inspect(|cur| {
             let diff = (
                 (self.end.0 - cur.0).clamp(-1, 1),
                 (self.end.1 - cur.1).clamp(-1, 1),
@@ -51,38 +57,43 @@ impl Iterator for LineSegIterator {
                 (0, dy) => { self.cur = Some((cur.0, cur.1 + dy)); }
                 (dx, _) => { self.cur = Some((cur.0 + dx, cur.1)); }
             }
-            cur
         })
     }
 }
 
-fn parse_input() -> Vec<Line> {
-    let (lines,) = InputParser::parse_file(Rule::input, "inputs/day14/input.txt");
-    lines
+fn parse_input(input: &str) -> Result<Vec<Line>> {
+    let (lines,) = InputParser::parse_str(Rule::input, input)?;
+    Ok(lines)
 }
 
-fn build_cave(lines: &Vec<Line>) -> Cave {
+fn build_cave(lines: &[Line]) -> Cave {
     let mut cave = Cave::new(Tile::Air);
     for line in lines {
-        let mut line_points = line.iter();
-        let mut start_point = line_points.next().unwrap();
-        for end_point in line_points {
-            for (col, row) in LineSegIterator::new(*start_point, *end_point) {
+        // A path of one point is a single rock.
+        if let [(col, row)] = line[..] {
+            cave.set(row, col, Tile::Rock);
+        }
+        for ends in line.windows(2) {
+            for (col, row) in LineSegIterator::new(ends[0], ends[1]) {
                cave.set(row, col, Tile::Rock);
             }
-            start_point = end_point;
         }
     }
+    // Make room for all the sand: it can't get below the row under the
+    // lowest rock, nor further sideways than it has fallen. validate has
+    // made sure there is some rock.
+    let floor = cave.max_row() + 1;
+    cave.set(SOURCE.0, SOURCE.1 - floor - 1, Tile::Air);
+    cave.set(floor, SOURCE.1 + floor + 1, Tile::Air);
     cave
 }
 
-fn tile_at(cave: &Cave, row: i32, col: i32) -> Tile {
-    cave.cursor_at(row, col).map(|c| *c).unwrap_or(Tile::Air)
-}
-
-fn move_sand(cave: &Cave, row: i32, col: i32) -> Option<(i32, i32)> {
-    [(row + 1, col), (row + 1, col -1), (row + 1, col + 1)].iter().copied()
-        .find(|(row, col)| tile_at(cave, *row, *col) == Tile::Air )
+// Moves the sand at `sand` one step, if it can, giving where it went.
+fn fall(mut sand: GridCursorMut<Cave>) -> Option<(i32, i32)> {
+    let fall = FALLS.into_iter().find(|fall| sand.neighbor(*fall) == Some(&Tile::Air))?;
+    sand.swap_with(fall);
+    sand.step(fall);
+    Some((sand.row, sand.col))
 }
 
 struct InfiniteSandDrip {
@@ -97,74 +108,77 @@ impl InfiniteSandDrip {
     }
 
     fn step(&mut self, cave: &mut Cave) {
-        if let Some((row, col)) = self.cur_sand.clone() {
+        if let Some((row, col)) = self.cur_sand {
             if row == cave.max_row() {
                 self.falling_off = true;
-                self.cur_sand = None;
-            }
-            self.cur_sand = move_sand(cave, row, col);
-            if let Some((new_row, new_col)) = self.cur_sand {
-                cave.set(row, col, Tile::Air);
-                cave.set(new_row, new_col, Tile::Sand);
             }
+            self.cur_sand = fall(cave.cursor_at_mut(row, col).unwrap());
         } else {
-            cave.set(0, 500, Tile::Sand);
+            cave.set(SOURCE.0, SOURCE.1, Tile::Sand);
             self.total += 1;
-            self.cur_sand = Some((0, 500));
+            self.cur_sand = Some(SOURCE);
         }
     }
 }
 
+// The cave's bottom row is just above the floor, so sand comes to rest
+// there as it does on rock.
 struct FloorSandDrip {
     total : usize,
-    floor : i32,
     blocked: bool,
     cur_sand : Option<(i32,i32)>,
 }
 
 impl FloorSandDrip {
-    fn new(floor: i32) -> Self {
-        Self{ total: 0, floor, blocked: false, cur_sand: None }
+    fn new() -> Self {
+        Self{ total: 0, blocked: false, cur_sand: None }
     }
 
     fn step(&mut self, cave: &mut Cave) {
-        if let Some((row, col)) = self.cur_sand.clone() {
-            self.cur_sand = move_sand(cave, row, col);
-            if let Some((new_row, new_col)) = self.cur_sand {
-                cave.set(row, col, Tile::Air);
-                cave.set(new_row, new_col, Tile::Sand);
-                if new_row == self.floor {
-                    self.cur_sand = None;
-                }
-            }
+        if let Some((row, col)) = self.cur_sand {
+            self.cur_sand = fall(cave.cursor_at_mut(row, col).unwrap());
+        } else if *cave.get(SOURCE.0, SOURCE.1) == Tile::Air {
+            cave.set(SOURCE.0, SOURCE.1, Tile::Sand);
+            self.total += 1;
+            self.cur_sand = Some(SOURCE);
         } else {
-            if tile_at(cave, 0, 500) == Tile::Air {
-                cave.set(0, 500, Tile::Sand);
-                self.total += 1;
-                self.cur_sand = Some((0, 500));
-            } else {
-                self.blocked = true;
-            }
+            self.blocked = true;
         }
     }
 }
 
-pub fn part1() {
-    let lines = parse_input();
-    let mut sand_drip = InfiniteSandDrip::new();
-    let mut cave = build_cave(&lines);
-    while ! sand_drip.falling_off {
-        sand_drip.step(&mut cave);
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input<'a> = Vec<Line>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Line>> {
+        parse_input(input)
+    }
+
+    fn validate(&self, lines: &Vec<Line>, validator: &mut InputValidator) {
+        validator.check(lines.iter().any(|line| !line.is_empty()), || "there is no rock".into());
+    }
+
+    fn part1(&self, lines: &Vec<Line>) -> Result<Answer> {
+        let mut sand_drip = InfiniteSandDrip::new();
+        let mut cave = build_cave(lines);
+        while ! sand_drip.falling_off {
+            sand_drip.step(&mut cave);
+        }
+        Ok((sand_drip.total - 1).into())
     }
-    println!("{}", sand_drip.total - 1);
-}
 
-pub fn part2() {
-    let lines = parse_input();
-    let mut cave = build_cave(&lines);
-    let mut sand_drip = FloorSandDrip::new(cave.max_row() + 1);
-    while ! sand_drip.blocked {
-        sand_drip.step(&mut cave);
+    fn part2(&self, lines: &Vec<Line>) -> Result<Answer> {
+        let mut cave = build_cave(lines);
+        let mut sand_drip = FloorSandDrip::new();
+        while ! sand_drip.blocked {
+            sand_drip.step(&mut cave);
+        }
+        Ok(sand_drip.total.into())
     }
-    println!("{}", sand_drip.total);
-}
\ No newline at end of file
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [24.into(), 93.into()]
+    }
+}
@@ -1,7 +1,9 @@
-use crate::parsing::ParseFile;
+use crate::parsing::{pest_grammar, ParseFile};
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar="src/day15.pest"]
+#[pest_grammar("src/day15.pest")]
 struct InputParser;
 
 #[derive(Clone, Debug)]
@@ -30,54 +32,88 @@ fn dist(from: (i32,i32), to: (i32,i32)) -> i32 {
     (from.0.abs_diff(to.0) + from.1.abs_diff(to.1)) as i32
 }
 
-fn parse_input() -> Vec<((i32,i32),(i32,i32))> {
-    let (input,) = InputParser::parse_file(Rule::input, "inputs/day15/input.txt");
-    return input;
+type Reading = ((i32,i32),(i32,i32));
+
+fn parse_input(input: &str) -> Result<Vec<Reading>> {
+    let (input,) = InputParser::parse_str(Rule::input, input)?;
+    Ok(input)
+}
+#[derive(Clone, Copy, Debug)]
+struct SearchArea {
+    row: i32,
+    max_size: i32,
 }
-const MAX_SIZE : i32 = 4000000;
-fn find_beacon(sensors: &Vec<Sensor>) -> (i32,i32) {
-    for x in 0..=MAX_SIZE {
+
+const AREA : SearchArea = SearchArea { row: 2000000, max_size: 4000000 };
+// The example asks about a smaller area than the real input.
+const EXAMPLE_AREA : SearchArea = SearchArea { row: 10, max_size: 20 };
+
+#[derive(Clone, Debug)]
+pub struct Scan {
+    readings: Vec<Reading>,
+    area: SearchArea,
+}
+
+fn find_beacon(sensors: &[Sensor], max_size: i32) -> Option<(i32,i32)> {
+    for x in 0..=max_size {
         let mut y = 0;
-        while y <= MAX_SIZE {
+        while y <= max_size {
             if let Some(s) = sensors.iter().find(|s| { s.in_radius(x,y) }) {
                 let skip = s.y + s.radius - (s.x.abs_diff(x) as i32) + 1;
                 y = skip;
             } else {
-                return (x,y);
+                return Some((x,y));
             }
         }
     }
-    unreachable!();
+    None
 }
 
 fn is_closer(sensor: ((i32,i32), (i32,i32)), beacon: (i32,i32)) -> bool {
     dist(sensor.0, beacon) <= dist(sensor.0, sensor.1)
 }
 
-pub fn part1() {
-    let input = parse_input();
-    let max_x = input.iter().map(|(sensor, beacon)| {
-        sensor.0 + dist(*sensor,*beacon)
-    }).max().unwrap();
-    let min_x = input.iter().map(|(sensor, beacon)| {
-        sensor.0 - dist(*sensor,*beacon)
-    }).min().unwrap();
-    let y = 2000000;
-    let blocked = (min_x..=max_x).filter(|x| {
-        input.iter().any(|sensor| {
-            let pos = (*x, y);
-             sensor.1 != pos &&
-            is_closer(*sensor, pos)
-        })
-    }).count();
-    println!("{}", blocked);
-}
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input<'a> = Scan;
 
+    fn parse(&self, input: &str) -> Result<Scan> {
+        Ok(Scan { readings: parse_input(input)?, area: AREA })
+    }
+
+    fn parse_example(&self, input: &str) -> Result<Scan> {
+        Ok(Scan { readings: parse_input(input)?, area: EXAMPLE_AREA })
+    }
+
+    fn part1(&self, scan: &Scan) -> Result<Answer> {
+        let input = &scan.readings;
+        let max_x = input.iter().map(|(sensor, beacon)| {
+            sensor.0 + dist(*sensor,*beacon)
+        }).max().ok_or(Error::no_solution("there are no sensors"))?;
+        let min_x = input.iter().map(|(sensor, beacon)| {
+            sensor.0 - dist(*sensor,*beacon)
+        }).min().ok_or(Error::no_solution("there are no sensors"))?;
+        let y = scan.area.row;
+        let blocked = (min_x..=max_x).filter(|x| {
+            input.iter().any(|sensor| {
+                let pos = (*x, y);
+                 sensor.1 != pos &&
+                is_closer(*sensor, pos)
+            })
+        }).count();
+        Ok(blocked.into())
+    }
 
-pub fn part2() {
-    let input = parse_input();
-    let sensors : Vec<_> = input.iter().map(|(s, b)| Sensor::new(*s, *b) ).collect();
-    let beacon = find_beacon(&sensors);
+    fn part2(&self, scan: &Scan) -> Result<Answer> {
+        let sensors : Vec<_> = scan.readings.iter().map(|(s, b)| Sensor::new(*s, *b) ).collect();
+        let beacon = find_beacon(&sensors, scan.area.max_size)
+            .ok_or(Error::no_solution("every position in the search area is covered"))?;
 
-    println!("{}", (beacon.0 as i64) * 4000000 + (beacon.1 as i64));
-}
\ No newline at end of file
+        Ok(((beacon.0 as i64) * 4000000 + (beacon.1 as i64)).into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [26.into(), 56000011.into()]
+    }
+}
@@ -1,39 +1,59 @@
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::collections::hash_map::Entry;
 use std::fmt::{Debug, Formatter};
 use std::hash::Hash;
+use std::iter::once;
 use std::mem::take;
 use std::ops::Deref;
 use std::rc::Rc;
 use itertools::Itertools;
-use pest::iterators::Pair;
-use crate::parsing::{FromPair, ParseFile, ParseNext};
+use crate::parsing::{pest_grammar, FromPair, ParseFile, Symbol, Symbols};
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use crate::solution::{InputValidator, Solution};
 use crate::weighted_value::WeightedValue;
 
-#[derive(Parser)]
-#[grammar="src/day16.pest"]
+#[pest_grammar("src/day16.pest")]
 struct InputParser;
 
+#[derive(FromPair)]
+struct ValveLine<'a> {
+    name: &'a str,
+    rate: u32,
+    tunnels: Vec<&'a str>,
+}
+
 #[derive(Clone, Hash, Eq, PartialEq)]
-struct Valve {
-    name: String,
+pub struct Valve {
+    name: Symbol,
     rate: u32,
     open: bool,
-    tunnels: Rc<Vec<String>>,
+    tunnels: Rc<Vec<Symbol>>,
+}
+
+pub struct Cave<'a> {
+    valves: Vec<Rc<Valve>>,
+    symbols: Symbols<'a>,
+}
+
+impl Cave<'_> {
+    fn start(&self) -> Result<Symbol> {
+        self.symbols.get("AA").ok_or_else(|| Error::no_solution("there is no valve AA to start from"))
+    }
 }
 
 impl Debug for Valve {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let open_state = if self.open { 'O'} else {'X'};
-        f.write_fmt(format_args!("{}({},{})", self.name, self.rate , open_state))
+        f.write_fmt(format_args!("{:?}({},{})", self.name, self.rate , open_state))
     }
 }
 
 #[derive(Clone, Hash, Eq, PartialEq)]
 struct CaveState {
     valves: Vec<Rc<Valve>>,
-    cur: String,
+    cur: Symbol,
 }
 
 impl CaveState {
@@ -50,11 +70,10 @@ impl CaveState {
 
     fn moves(&self) -> Vec<Self> {
         self.deref().tunnels.iter().map(|name| {
-            let iter = Self {
+            Self {
                 valves: self.valves.clone(),
-                cur: name.clone(),
-            };
-            iter
+                cur: *name,
+            }
         }).collect()
     }
 
@@ -66,7 +85,7 @@ impl CaveState {
                 valve.clone()
             }
         }).collect();
-        Self{ valves, cur: self.cur.clone() }
+        Self{ valves, cur: self.cur }
     }
 }
 
@@ -98,13 +117,13 @@ impl Deref for CaveState {
 #[derive(Clone, Hash, Eq, PartialEq)]
 struct MultiCaveState {
     valves: Vec<Rc<Valve>>,
-    cur: (String,String),
+    cur: (Symbol, Symbol),
 }
 
 #[derive(Clone, Eq, PartialEq)]
 enum Action {
     Open,
-    Move(String),
+    Move(Symbol),
 }
 
 #[derive(Clone, Debug)]
@@ -142,8 +161,8 @@ impl Ord for Output {
 
 
 impl MultiCaveState {
-    fn get(&self, name: &String) -> &Valve {
-        self.valves.iter().find(|v| &v.name == name ).unwrap()
+    fn get(&self, name: Symbol) -> &Valve {
+        self.valves.iter().find(|v| v.name == name ).unwrap()
     }
 
     fn done(&self) -> bool {
@@ -157,9 +176,9 @@ impl MultiCaveState {
             .sum()
     }
 
-    fn actions(&self, name: &String) -> Vec<Action> {
+    fn actions(&self, name: Symbol) -> Vec<Action> {
         let valve = self.get(name);
-        let mut actions : Vec<_> = valve.tunnels.iter().cloned().map(|n| Action::Move(n)).collect();
+        let mut actions : Vec<_> = valve.tunnels.iter().copied().map(Action::Move).collect();
         if !valve.open && valve.rate > 0 {
             actions.push(Action::Open);
         }
@@ -169,8 +188,8 @@ impl MultiCaveState {
     fn next_states(&self) -> Vec<Self> {
         if self.done() { return vec![self.clone()] }
 
-        let first_actions = self.actions(&self.cur.0).into_iter();
-        let second_actions = self.actions(&self.cur.1).into_iter();
+        let first_actions = self.actions(self.cur.0).into_iter();
+        let second_actions = self.actions(self.cur.1).into_iter();
 
         first_actions.cartesian_product(second_actions)
             .filter(|(m,e)| {
@@ -182,16 +201,15 @@ impl MultiCaveState {
 
     fn action(&self, a: Action, b: Action) -> Self {
         let valves = self.valves.iter().map(|valve| {
-            if a == Action::Open && valve.name == self.cur.0 {
-                Rc::new(Valve { open: true, ..(**valve).clone() })
-            } else if b == Action::Open && valve.name == self.cur.1 {
+            if (a == Action::Open && valve.name == self.cur.0)
+                || (b == Action::Open && valve.name == self.cur.1) {
                 Rc::new(Valve { open: true, ..(**valve).clone() })
             } else {
                 valve.clone()
             }
         }).collect();
-        let cur_0 = if let Action::Move(name) = a { name } else { self.cur.0.clone() };
-        let cur_1 = if let Action::Move(name) = b { name } else { self.cur.1.clone() };
+        let cur_0 = if let Action::Move(name) = a { name } else { self.cur.0 };
+        let cur_1 = if let Action::Move(name) = b { name } else { self.cur.1 };
         let cur = if cur_0 < cur_1 { (cur_0, cur_1) } else { (cur_1, cur_0) };
         Self {
             valves,
@@ -218,18 +236,6 @@ impl Debug for MultiCaveState {
     }
 }
 
-impl FromPair<Rule> for Valve {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        Self {
-            name: pairs.parse_next(),
-            rate: pairs.parse_next(),
-            tunnels: pairs.parse_next(),
-            open: false,
-        }
-    }
-}
-
 fn update_state<T: Eq + Hash + Clone, O: Clone + Ord>(scores: &mut HashMap<T, O>, open: &mut VecDeque<(T, O)>, state: &T, total: O) {
     match scores.entry(state.clone()) {
         Entry::Occupied(mut o) => {
@@ -269,63 +275,88 @@ fn update_state2(scores: &mut HashMap<MultiCaveState, Output>, open: &mut Binary
     }
 }
 
-fn parse_input() -> Vec<Rc<Valve>> {
-    let (input,) = InputParser::parse_file(Rule::input, "inputs/day16/input.txt");
-    input
+fn parse_input(input: &str) -> Result<Cave<'_>> {
+    let (lines,) : (Vec<ValveLine>,) = InputParser::parse_str(Rule::input, input)?;
+    let symbols = Symbols::new(lines.iter().flat_map(|line| once(line.name).chain(line.tunnels.iter().copied())));
+    let symbol = |name| symbols.get(name).unwrap();
+    let valves = lines.iter().map(|line| Rc::new(Valve {
+        name: symbol(line.name),
+        rate: line.rate,
+        open: false,
+        tunnels: Rc::new(line.tunnels.iter().map(|tunnel| symbol(tunnel)).collect()),
+    })).collect();
+    Ok(Cave { valves, symbols })
 }
 
-pub fn part1() {
-    let valves = parse_input();
-    let mut scores : HashMap<CaveState, u32> = HashMap::new();
-    let start = CaveState { valves: valves.clone(), cur: "AA".into() };
-    let mut open : VecDeque<(CaveState, u32)> = VecDeque::new();
-    open.push_back((start.clone(), 0));
-    scores.insert(start.clone(), 0);
-    for i in 1..=30 {
-        println!("{}: {} open", i, open.len());
-        let cur_open = take(&mut open);
-        for (state, score) in &cur_open {
-            let total = score + state.current_rate();
-            if state.done() {
-                update_state(&mut scores, &mut open, state, total);
-            } else {
-                if !state.open && state.rate > 0 {
-                    let open_state = state.open();
-                    update_state(&mut scores, &mut open, &open_state, total);
-                }
-                for move_state in state.moves() {
-                    update_state(&mut scores, &mut open, &move_state, total);
-                }
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input<'a> = Cave<'a>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Cave<'a>> {
+        parse_input(input)
+    }
+
+    fn validate(&self, cave: &Cave<'_>, validator: &mut InputValidator) {
+        let names : HashSet<Symbol> = cave.valves.iter().map(|v| v.name).collect();
+        let has_start = cave.symbols.get("AA").is_some_and(|start| names.contains(&start));
+        validator.check(has_start, || "there is no valve AA to start from".into());
+        validator.check(names.len() == cave.valves.len(), || "valve names must be unique".into());
+        for valve in &cave.valves {
+            for tunnel in valve.tunnels.iter().filter(|t| !names.contains(t)) {
+                validator.problem(format!("valve {} leads to unknown valve {}",
+                                          cave.symbols.name(valve.name), cave.symbols.name(*tunnel)));
             }
         }
     }
-    let (state, score) = scores.iter().max_by(|(_,a),(_,b)| a.cmp(b) ).unwrap();
-    println!("Final {}: {:?}", score, state);
-}
 
-pub fn part2() {
-    let valves = parse_input();
-    let mut scores : HashMap<MultiCaveState, Output> = HashMap::new();
-    let start = MultiCaveState { valves: valves.clone(), cur: ("AA".into(), "AA".into()) };
-    let mut open : BinaryHeap<HeapValue> = Default::default();
-    let initial_output = Output { minute: 0, initial: 0, rate: 0};
-    open.push(WeightedValue::new((start.clone(), initial_output.clone()), initial_output));
-    scores.insert(start.clone(), Output { minute: 0, initial: 0, rate: 0});
-    let mut counter: usize = 0;
-    while let Some(WeightedValue { weight: _, value: (state, score) }) = open.pop() {
-        if counter % 1000000 == 0 {
-            println!("{}: {} open", counter, open.len());
-        }
-        counter += 1;
-        let minute = score.minute + 1;
-        if minute > 26 {
-            break;
+    fn part1(&self, cave: &Cave<'_>) -> Result<Answer> {
+        let mut scores : HashMap<CaveState, u32> = HashMap::new();
+        let start = CaveState { valves: cave.valves.clone(), cur: cave.start()? };
+        let mut open : VecDeque<(CaveState, u32)> = VecDeque::new();
+        open.push_back((start.clone(), 0));
+        scores.insert(start, 0);
+        for _ in 1..=30 {
+            let cur_open = take(&mut open);
+            for (state, score) in &cur_open {
+                let total = score + state.current_rate();
+                if state.done() {
+                    update_state(&mut scores, &mut open, state, total);
+                } else {
+                    if !state.open && state.rate > 0 {
+                        let open_state = state.open();
+                        update_state(&mut scores, &mut open, &open_state, total);
+                    }
+                    for move_state in state.moves() {
+                        update_state(&mut scores, &mut open, &move_state, total);
+                    }
+                }
+            }
         }
+        Ok((*scores.values().max().unwrap()).into())
+    }
+
+    fn part2(&self, cave: &Cave<'_>) -> Result<Answer> {
+        let mut scores : HashMap<MultiCaveState, Output> = HashMap::new();
+        let start = MultiCaveState { valves: cave.valves.clone(), cur: (cave.start()?, cave.start()?) };
+        let mut open : BinaryHeap<HeapValue> = Default::default();
+        let initial_output = Output { minute: 0, initial: 0, rate: 0};
+        open.push(WeightedValue::new((start.clone(), initial_output.clone()), initial_output));
+        scores.insert(start, Output { minute: 0, initial: 0, rate: 0});
+        while let Some(WeightedValue { weight: _, value: (state, score) }) = open.pop() {
+            let minute = score.minute + 1;
+            if minute > 26 {
+                break;
+            }
 
-        for new_state in &state.next_states() {
-            update_state2(&mut scores, &mut open, new_state, score.clone());
+            for new_state in &state.next_states() {
+                update_state2(&mut scores, &mut open, new_state, score.clone());
+            }
         }
+        Ok(scores.values().max().unwrap().total_at(26).into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [1651.into(), 1707.into()]
     }
-    let (state, score) = scores.iter().max_by(|(_,a),(_,b)| a.cmp(b) ).unwrap();
-    println!("Final {}: {:?}", score.total_at(26), state);
-}
\ No newline at end of file
+}
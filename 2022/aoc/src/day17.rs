@@ -1,9 +1,12 @@
 use std::cmp::max;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Write};
-use std::fs::read_to_string;
 use std::str::FromStr;
 use itertools::Itertools;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::parsing::lines::{lines, Line};
+use crate::solution::Solution;
 
 #[derive(Clone, Copy, Default, PartialEq, Eq)]
 enum Cell {
@@ -70,17 +73,17 @@ impl Rock {
 }
 
 #[derive(Clone, Copy)]
-enum Move {
+pub enum Move {
     Left,
     Right,
 }
 
 impl Move {
-    fn from_char(ch: char) -> Self {
+    fn from_char(ch: char) -> Option<Self> {
         match ch {
-            '>' => Move::Right,
-            '<' => Move::Left,
-            _ => panic!("Invalid char")
+            '>' => Some(Move::Right),
+            '<' => Some(Move::Left),
+            _ => None
         }
     }
 
@@ -136,7 +139,7 @@ impl Simulation {
         }
     }
 
-    fn new_run(&self) -> Run {
+    fn new_run(&self) -> Run<'_> {
         Run::new(self)
     }
 }
@@ -208,7 +211,7 @@ impl<'a> Run<'a> {
 
     fn try_move<F: FnOnce(Pos) -> Pos>(&mut self, f: F) -> bool {
         self.unset_rock_cells();
-        let old_pos = self.cur_rock.pos.clone();
+        let old_pos = self.cur_rock.pos;
         self.cur_rock.pos = f(old_pos);
         if self.is_possible_move() {
             self.set_rock_cells();
@@ -261,61 +264,76 @@ impl<'a> Display for Run<'a> {
     }
 }
 
-fn detect_loop(diffs: &Vec<usize>, start: usize, size: usize) -> bool {
+fn detect_loop(diffs: &[usize], start: usize, size: usize) -> bool {
     let mut values : Vec<usize> = Vec::new();
     let mut index = start;
     while (index + size) < diffs.len() {
         values.push(diffs[index..(index+size)].iter().sum());
         index += size;
     }
-    values.iter().all(|v| v == values.first().unwrap()) && {
-        println!("Found loop: (start: {}, size: {}) {} times", start, size, values.len());
-        true
-    }
+    values.iter().all(|v| v == values.first().unwrap())
 }
 
-fn parse_input() -> Vec<Move> {
-    read_to_string("inputs/day17/input.txt")
-        .unwrap().lines().next().unwrap().chars().map(|c| Move::from_char(c)).collect()
+fn parse_input(input: &str) -> Result<Vec<Move>, ParseError> {
+    let line = lines(input).next().unwrap_or(Line { number: 1, text: "" });
+    if line.text.is_empty() {
+        return Err(line.error(1, "expected a line of jet pushes"));
+    }
+    line.text.chars().enumerate()
+        .map(|(i, ch)| Move::from_char(ch).ok_or_else(|| line.error(i + 1, format!("expected '<' or '>', found {:?}", ch))))
+        .collect()
 }
 
-pub fn part1() {
-    let moves = parse_input();
-    let simulation = Simulation::new(moves);
-    let mut run = simulation.new_run();
-    while run.num_rocks < 2022 {
-        run.step();
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input<'a> = Vec<Move>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Move>> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(&self, moves: &Vec<Move>) -> Result<Answer> {
+        let simulation = Simulation::new(moves.clone());
+        let mut run = simulation.new_run();
+        while run.num_rocks < 2022 {
+            run.step();
+        }
+        Ok(run.highest_point.into())
+    }
+
+    fn part2(&self, moves: &Vec<Move>) -> Result<Answer> {
+        let simulation = Simulation::new(moves.clone());
+        let mut run = simulation.new_run();
+        let num_shapes = simulation.shapes.len();
+        let high_points : Vec<_> = (0..(10000 * num_shapes)).map(|_| {
+            run.run_until_next_rock();
+            run.highest_point
+        }).collect();
+        let mut high_point = 0;
+        let diffs : Vec<_>= high_points.iter().map(|hp| {
+            let uhp = *hp as usize;
+            let diff = uhp - high_point;
+            high_point = uhp;
+            diff
+        }).collect();
+        let (start, size) = (0..500).cartesian_product(1..400)
+            .map(|(start,size)| (start * num_shapes, size * num_shapes) )
+            .find(|(start, size)| detect_loop(&diffs, *start , *size))
+            .ok_or(Error::no_solution("the tower never repeats"))?;
+        let mut rocks_left = 1000000000000;
+        let start_height : usize = diffs[0..start].iter().sum();
+        rocks_left -= start;
+
+        let loop_height : usize = diffs[start..(start + size)].iter().sum();
+        let loops = rocks_left / size;
+        rocks_left -= loops * size;
+        let rest_height: usize = diffs[start..(start + rocks_left)].iter().sum();
+        let height = start_height + (loop_height * loops) + rest_height;
+        Ok(height.into())
     }
-    println!("{}", run.highest_point);
-}
 
-pub fn part2() {
-    let moves = parse_input();
-    let simulation = Simulation::new(moves);
-    let mut run = simulation.new_run();
-    let num_shapes = simulation.shapes.len();
-    let high_points : Vec<_> = (0..(10000 * num_shapes)).map(|_| {
-        run.run_until_next_rock();
-        run.highest_point
-    }).collect();
-    let mut high_point = 0;
-    let diffs : Vec<_>= high_points.iter().map(|hp| {
-        let uhp = *hp as usize;
-        let diff = uhp - high_point;
-        high_point = uhp;
-        diff
-    }).collect();
-    let (start, size) = (0..500).cartesian_product(1..400)
-        .map(|(start,size)| (start * num_shapes, size * num_shapes) )
-        .find(|(start, size)| detect_loop(&diffs, *start , *size)).unwrap();
-    let mut rocks_left = 1000000000000;
-    let start_height : usize = diffs[0..start].iter().sum();
-    rocks_left -= start;
-
-    let loop_height : usize = diffs[start..(start + size)].iter().sum();
-    let loops = rocks_left / size;
-    rocks_left -= loops * size;
-    let rest_height: usize = diffs[start..(start + rocks_left)].iter().sum();
-    let height = start_height + (loop_height * loops) + rest_height;
-    println!("{} = {} + ({} * {}) + {}", height, start_height, loop_height, loops, rest_height);
-}
\ No newline at end of file
+    fn example_answers(&self) -> [Answer; 2] {
+        [3068.into(), 1514285714288i64.into()]
+    }
+}
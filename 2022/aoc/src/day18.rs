@@ -1,14 +1,15 @@
 use std::cmp::{max, min};
 use std::collections::HashSet;
-use pest::iterators::Pair;
-use crate::parsing::{FromPair, ParseFile, ParseNext};
+use crate::parsing::{pest_grammar, FromPair, ParseFile};
+use crate::answer::Answer;
+use crate::error::Result;
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar="src/day18.pest"]
+#[pest_grammar("src/day18.pest")]
 struct InputParser;
 
-#[derive(Clone, Debug, Hash, Eq, PartialEq)]
-struct Coord {
+#[derive(Clone, Debug, Hash, Eq, PartialEq, FromPair)]
+pub struct Coord {
     x: i32,
     y: i32,
     z: i32,
@@ -39,19 +40,8 @@ impl Coord {
     }
 }
 
-impl FromPair<Rule> for Coord {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        Self {
-            x: pairs.parse_next(),
-            y: pairs.parse_next(),
-            z: pairs.parse_next(),
-        }
-
-    }
-}
-fn parse_input() -> HashSet<Coord> {
-    InputParser::parse_file(Rule::input, "inputs/day18/input.txt")
+fn parse_input(input: &str) -> Result<HashSet<Coord>> {
+    Ok(InputParser::parse_str(Rule::input, input)?)
 }
 
 fn detect_air(lava: &HashSet<Coord>) -> HashSet<Coord> {
@@ -76,22 +66,34 @@ fn detect_air(lava: &HashSet<Coord>) -> HashSet<Coord> {
     air
 }
 
-pub fn part1() {
-    let coords = parse_input();
-    let total : usize = coords.iter().map(|c| {
-      c.sides().iter().filter(|c| !coords.contains(c) ).count()
-    }).sum();
+pub struct Day18;
 
-    println!("{}", total);
-}
+impl Solution for Day18 {
+    type Input<'a> = HashSet<Coord>;
 
-pub fn part2() {
-    let input = parse_input();
-    let air = detect_air(&input);
+    fn parse(&self, input: &str) -> Result<HashSet<Coord>> {
+        parse_input(input)
+    }
 
-    let total : usize = input.iter().map(|c| {
-        c.sides().iter().filter(|c| air.contains(c) ).count()
-    }).sum();
+    fn part1(&self, coords: &HashSet<Coord>) -> Result<Answer> {
+        let total : usize = coords.iter().map(|c| {
+          c.sides().iter().filter(|c| !coords.contains(c) ).count()
+        }).sum();
 
-    println!("{}", total);
-}
\ No newline at end of file
+        Ok(total.into())
+    }
+
+    fn part2(&self, input: &HashSet<Coord>) -> Result<Answer> {
+        let air = detect_air(input);
+
+        let total : usize = input.iter().map(|c| {
+            c.sides().iter().filter(|c| air.contains(c) ).count()
+        }).sum();
+
+        Ok(total.into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [64.into(), 58.into()]
+    }
+}
@@ -2,11 +2,13 @@ use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 use std::collections::hash_map::Entry;
 use pest::iterators::Pair;
-use crate::parsing::{FromPair, ParseFile, ParseNext};
+use crate::parsing::{pair_error, pest_grammar, FromPair, ParseFile, TryFromPair};
 use crate::quad::Quad;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar="src/day19.pest"]
+#[pest_grammar("src/day19.pest")]
 struct InputParser;
 
 #[derive(Copy, Clone, Debug)]
@@ -22,47 +24,39 @@ use crate::weighted_value::WeightedValue;
 
 type Count = u32;
 
-impl Into<usize> for Resource {
-    fn into(self) -> usize {
-       self as usize
+impl From<Resource> for usize {
+    fn from(resource: Resource) -> Self {
+       resource as usize
     }
 }
 
-impl FromPair<Rule> for Resource {
-    fn from_pair(pair: Pair<Rule>) -> Self {
+impl TryFromPair<'_, Rule> for Resource {
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<Rule>) -> Result<Self, ParseError> {
         match pair.as_str() {
-            "ore" => Self::Ore,
-            "clay" => Self::Clay,
-            "obsidian" => Self::Obsidian,
-            "geode" => Self::Geode,
-            r => panic!("Invalid resource: {}", r),
+            "ore" => Ok(Self::Ore),
+            "clay" => Ok(Self::Clay),
+            "obsidian" => Ok(Self::Obsidian),
+            "geode" => Ok(Self::Geode),
+            r => Err(pair_error(&pair, format!("Invalid resource: {}", r))),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, FromPair)]
 struct Robot {
     resource: Resource,
     costs: Vec<(Count, Resource)>
 }
 
-impl FromPair<Rule> for Robot {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut pairs = pair.into_inner();
-        Self {
-            resource: pairs.parse_next(),
-            costs: pairs.parse_next(),
-        }
-    }
-}
 
-
-struct Blueprint {
+pub struct Blueprint {
     robots: Quad<Quad<Count>>,
 }
 
 impl Blueprint {
-    fn from_input(input: &Vec<Robot>) -> Self {
+    fn from_input(input: &[Robot]) -> Self {
         let mut robots: Quad<Quad<Count>> = Default::default();
         for robot in input {
             robots[robot.resource as usize] = Quad::from_input(&robot.costs);
@@ -110,7 +104,6 @@ impl Blueprint {
         queue.push(WeightedValue::new((0,state), num_minutes * 8));
         scores.insert(state, 0);
         scores2.insert((0, state.robots), state.resources);
-        let mut counter: usize = 0;
         let mut max_geodes : u32 = 0;
         while let Some(val)  = queue.pop() {
             let (minute, state) = val.value;
@@ -119,10 +112,6 @@ impl Blueprint {
                 max_geodes = geodes;
             }
             if minute < num_minutes {
-                if counter % 1000000 == 0 {
-                    println!("{}: {} open", counter, queue.len());
-                }
-                counter += 1;
                 let next_min = minute + 1;
                 self.for_each_action(state, |next_state| {
                     let score = (num_minutes - next_min) * 8 + (next_state.resources[Geode.into()] as usize);
@@ -131,7 +120,8 @@ impl Blueprint {
                     let has_best_score2 = match best_score2 {
                         Entry::Occupied(mut e) => {
                             let cur = e.get_mut();
-                            !(*cur >= next_state.resources) && {
+                            let dominated = *cur >= next_state.resources;
+                            !dominated && {
                                 *cur = next_state.resources;
                                 true
                             }
@@ -148,7 +138,7 @@ impl Blueprint {
                 break;
             }
         }
-        return max_geodes;
+        max_geodes
     }
 }
 
@@ -181,31 +171,36 @@ impl Ord for State {
     }
 }
 
-fn read_input() -> Vec<Blueprint> {
-    let (inputs,) : (Vec<_>,) = InputParser::parse_file(Rule::input, "inputs/day19/input.txt");
-    inputs.iter().map(|input| Blueprint::from_input(input)).collect()
+fn read_input(input: &str) -> Result<Vec<Blueprint>> {
+    let (inputs,) : (Vec<Vec<Robot>>,) = InputParser::parse_str(Rule::input, input)?;
+    Ok(inputs.iter().map(|input| Blueprint::from_input(input)).collect())
 }
 
-pub fn part1() {
-    let blueprints = read_input();
-    let amounts : Vec<_> = blueprints.iter().map(|blueprint| {
-        let geodes = blueprint.calculate_max_geodes(24);
-        println!("{} geodes", geodes);
-        geodes
-    }).collect();
-    println!("{:?}", amounts);
-    let score : usize = amounts.iter().enumerate().map(|(i,a)| (i+1) * (*a as usize)).sum();
-    println!("{}", score);
-}
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input<'a> = Vec<Blueprint>;
 
-pub fn part2() {
-    let blueprints = read_input();
-    let amounts : Vec<_> = blueprints.iter().take(3).map(|blueprint| {
-        let geodes = blueprint.calculate_max_geodes(32);
-        println!("{} geodes", geodes);
-        geodes
-    }).collect();
-    println!("{:?}", amounts);
-    let score : u32 = amounts.into_iter().product();
-    println!("{}", score);
-}
\ No newline at end of file
+    fn parse(&self, input: &str) -> Result<Vec<Blueprint>> {
+        read_input(input)
+    }
+
+    fn part1(&self, blueprints: &Vec<Blueprint>) -> Result<Answer> {
+        let amounts : Vec<_> = blueprints.iter()
+            .map(|blueprint| blueprint.calculate_max_geodes(24))
+            .collect();
+        let score : usize = amounts.iter().enumerate().map(|(i,a)| (i+1) * (*a as usize)).sum();
+        Ok(score.into())
+    }
+
+    fn part2(&self, blueprints: &Vec<Blueprint>) -> Result<Answer> {
+        let score : u32 = blueprints.iter().take(3)
+            .map(|blueprint| blueprint.calculate_max_geodes(32))
+            .product();
+        Ok(score.into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [33.into(), 3472.into()]
+    }
+}
@@ -1,37 +1,51 @@
-use crate::util::read_lines;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::parsing::lines::lines;
+use crate::solution::Solution;
 
 #[derive(Copy, Clone, Debug)]
-enum Throw { Rock, Paper, Scissors }
+pub enum Throw { Rock, Paper, Scissors }
 use Throw::{Rock, Paper, Scissors};
 
 #[derive(Copy, Clone, Debug)]
 enum Outcome { Win, Tie, Loss }
 use Outcome::{Win, Tie, Loss};
 
-fn parse_opp_throw(str: &str) -> Throw {
+fn parse_opp_throw(str: &str) -> Option<Throw> {
     match str {
-        "A" => Rock,
-        "B" => Paper,
-        "C" => Scissors,
-        _ => unreachable!(),
+        "A" => Some(Rock),
+        "B" => Some(Paper),
+        "C" => Some(Scissors),
+        _ => None,
     }
 }
 
-fn parse_my_throw(str: &str) -> Throw {
+// The second column means a throw in part 1 and an outcome in part 2.
+#[derive(Copy, Clone, Debug)]
+pub enum Code { X, Y, Z }
+
+fn parse_code(str: &str) -> Option<Code> {
     match str {
-        "X" => Rock,
-        "Y" => Paper,
-        "Z" => Scissors,
-        _ => unreachable!(),
+        "X" => Some(Code::X),
+        "Y" => Some(Code::Y),
+        "Z" => Some(Code::Z),
+        _ => None,
     }
 }
 
-fn parse_outcome(str: &str) -> Outcome {
-    match str {
-        "X" => Loss,
-        "Y" => Tie,
-        "Z" => Win,
-        _ => unreachable!(),
+fn my_throw(code: Code) -> Throw {
+    match code {
+        Code::X => Rock,
+        Code::Y => Paper,
+        Code::Z => Scissors,
+    }
+}
+
+fn outcome(code: Code) -> Outcome {
+    match code {
+        Code::X => Loss,
+        Code::Y => Tie,
+        Code::Z => Win,
     }
 }
 
@@ -66,34 +80,45 @@ fn determine_throw(opp_throw: Throw, outcome: Outcome) -> Throw {
     }
 }
 
-fn read_part1() -> Vec<(Throw, Throw)> {
-    let lines = read_lines("inputs/day2/part1.txt");
-    lines.iter().map(|line| {
-        let mut throws = line.split(" ");
-        (parse_opp_throw(&throws.next().unwrap()), parse_my_throw(&throws.next().unwrap()))
-    }).collect()
-}
+type Guide = Vec<(Throw, Code)>;
 
-fn read_part2() -> Vec<(Throw, Outcome)> {
-    let lines = read_lines("inputs/day2/part2.txt");
-    lines.iter().map(|line| {
-        let mut throws = line.split(" ");
-        (parse_opp_throw(&throws.next().unwrap()), parse_outcome(&throws.next().unwrap()))
+fn read_guide(input: &str) -> Result<Guide, ParseError> {
+    lines(input).map(|line| {
+        let [opp, code] = line.fields("{} {}")?;
+        let throw = parse_opp_throw(opp)
+            .ok_or_else(|| line.error_at(opp, format!("expected A, B or C, found {:?}", opp)))?;
+        let code = parse_code(code)
+            .ok_or_else(|| line.error_at(code, format!("expected X, Y or Z, found {:?}", code)))?;
+        Ok((throw, code))
     }).collect()
 }
 
-pub fn part1() {
-    let score : i32 = read_part1().iter()
-        .map(|(opp, me)| score(*opp, *me))
-        .sum();
+pub struct Day2;
 
-    println!("{}", score);
-}
+impl Solution for Day2 {
+    type Input<'a> = Guide;
 
-pub fn part2() {
-    let score : i32 = read_part2().iter()
-        .map(|(opp, outcome)| score(*opp, determine_throw(*opp, *outcome)))
-        .sum();
+    fn parse(&self, input: &str) -> Result<Guide> {
+        Ok(read_guide(input)?)
+    }
+
+    fn part1(&self, guide: &Guide) -> Result<Answer> {
+        let score : i32 = guide.iter()
+            .map(|(opp, code)| score(*opp, my_throw(*code)))
+            .sum();
+
+        Ok(score.into())
+    }
 
-    println!("{}", score);
+    fn part2(&self, guide: &Guide) -> Result<Answer> {
+        let score : i32 = guide.iter()
+            .map(|(opp, code)| score(*opp, determine_throw(*opp, outcome(*code))))
+            .sum();
+
+        Ok(score.into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [15.into(), 12.into()]
+    }
 }
@@ -1,5 +1,8 @@
-use std::fs::read_to_string;
 use std::ops::Index;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::parsing::lines::lines;
+use crate::solution::{InputValidator, Solution};
 
 #[derive(Clone, Debug)]
 struct Node {
@@ -52,6 +55,10 @@ impl List {
     }
 
     fn move_by(&mut self, ind: usize, mut amount: i64) {
+        // A number on its own has nowhere to go.
+        if self.len() <= 1 {
+            return;
+        }
         amount %= (self.len() as i64) - 1;
         if amount < 0 {
             for _ in amount..0 { self.move_back(ind); }
@@ -108,36 +115,56 @@ impl Index<usize> for List {
     }
 }
 
-fn read_input() -> Vec<i64> {
-    read_to_string("inputs/day20/input.txt").unwrap()
-        .lines().map(|s| s.parse().unwrap() ).collect()
+fn read_input(input: &str) -> Result<Vec<i64>, ParseError> {
+    lines(input).map(|line| line.parse()).collect()
 }
 
-pub fn part1() {
-    let input = read_input();
-    let mut list = List::new(input.clone());
-    for (ind, amount) in input.iter().enumerate() {
-        list.move_by(ind, *amount);
-    }
-    let zero = list.find(0).unwrap();
-    let total :i64 = [1000,2000,3000].iter().map(|amount| {
-        list[list.advance(zero, *amount)]
-    }).sum();
-    println!("{}", total);
-}
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input<'a> = Vec<i64>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i64>> {
+        Ok(read_input(input)?)
+    }
+
+    fn validate(&self, input: &Vec<i64>, validator: &mut InputValidator) {
+        let zeros : Vec<String> = input.iter().enumerate()
+            .filter(|(_, value)| **value == 0)
+            .map(|(i, _)| format!("line {}", i + 1))
+            .collect();
+        validator.exactly_one("0", &zeros);
+    }
 
-pub fn part2() {
-    let decryption_key = 811589153;
-    let input : Vec<_> = read_input().iter().map(|v| v * decryption_key).collect();
-    let mut list = List::new(input.clone());
-    for _ in 0..10 {
+    fn part1(&self, input: &Vec<i64>) -> Result<Answer> {
+        let mut list = List::new(input.clone());
         for (ind, amount) in input.iter().enumerate() {
             list.move_by(ind, *amount);
         }
+        let zero = list.find(0).ok_or(Error::no_solution("there is no 0 in the file"))?;
+        let total :i64 = [1000,2000,3000].iter().map(|amount| {
+            list[list.advance(zero, *amount)]
+        }).sum();
+        Ok(total.into())
+    }
+
+    fn part2(&self, input: &Vec<i64>) -> Result<Answer> {
+        let decryption_key = 811589153;
+        let input : Vec<_> = input.iter().map(|v| v * decryption_key).collect();
+        let mut list = List::new(input.clone());
+        for _ in 0..10 {
+            for (ind, amount) in input.iter().enumerate() {
+                list.move_by(ind, *amount);
+            }
+        }
+        let zero = list.find(0).ok_or(Error::no_solution("there is no 0 in the file"))?;
+        let total :i64 = [1000,2000,3000].iter().map(|amount| {
+            list[list.advance(zero, *amount)]
+        }).sum();
+        Ok(total.into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [3.into(), 1623178306.into()]
     }
-    let zero = list.find(0).unwrap();
-    let total :i64 = [1000,2000,3000].iter().map(|amount| {
-        list[list.advance(zero, *amount)]
-    }).sum();
-    println!("{}", total);
 }
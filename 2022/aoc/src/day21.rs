@@ -1,10 +1,11 @@
-use std::collections::HashMap;
-use std::ops::{Add, Sub, Mul, Div};
+use std::collections::{HashMap, HashSet};
 use pest::iterators::Pair;
-use crate::parsing::{FromPair, ParseFile, ParseInto, ParseNext};
+use crate::parsing::{pair_error, pest_grammar, FromPair, ParseFile, TryFromPair};
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::solution::{InputValidator, Solution};
 
-#[derive(Parser)]
-#[grammar="src/day21.pest"]
+#[pest_grammar("src/day21.pest")]
 struct InputParser;
 
 type Num = i64;
@@ -15,70 +16,41 @@ enum Val {
     Var(Vec<(Op, i64)>)
 }
 impl Val {
-    fn num(&self) -> i64 {
-        if let Self::Num(n) = self {
-            *n
-        } else {
-            panic!("Not a num: {:?}", self);
+    fn num(&self) -> Result<i64> {
+        match self {
+            Self::Num(n) => Ok(*n),
+            Self::Var(_) => Err(Error::no_solution("the answer depends on humn")),
         }
-
     }
-    fn op<F: FnOnce(i64, i64) -> i64>(self, rhs: Self, op: Op, f: F) -> Self {
-        match (self.clone(), rhs.clone()) {
-            (Self::Num(a), Self::Num(b)) => { Self::Num(f(a, b)) },
+    fn op<F: FnOnce(i64, i64) -> Option<i64>>(self, rhs: Self, op: Op, f: F) -> Result<Self> {
+        match (self, rhs) {
+            (Self::Num(a), Self::Num(b)) => {
+                f(a, b).map(Self::Num).ok_or_else(|| Error::no_solution(format!("can't work out {} {:?} {}", a, op, b)))
+            },
             (Self::Var(mut reverse_ops), Self::Num(n)) => {
                 op.reverse_rhs(n, &mut reverse_ops);
-                Self::Var(reverse_ops)
+                Ok(Self::Var(reverse_ops))
             }
             (Self::Num(n), Self::Var(mut reverse_ops)) => {
                 op.reverse_lhs(n, &mut reverse_ops);
-                Self::Var(reverse_ops)
+                Ok(Self::Var(reverse_ops))
             }
-            (_, _) => panic!("Can't do op on 2 vars:\n{:?}\n{:?}", self, rhs)
+            (Self::Var(_), Self::Var(_)) => Err(Error::no_solution(format!("humn is on both sides of a {:?}", op))),
         }
     }
 
-    fn solve(&self, equals: i64) -> Option<i64> {
-        if let Self::Var(ops) = self {
-            Some(ops.iter().rev().fold(equals, |lhs,(op, rhs)| {
-               op.apply(Self::Num(lhs), Self::Num(*rhs)).num()
-            }))
-        } else {
-            None
+    fn solve(&self, equals: i64) -> Result<i64> {
+        match self {
+            Self::Var(ops) => ops.iter().rev().try_fold(equals, |lhs, (op, rhs)| {
+                op.apply(Self::Num(lhs), Self::Num(*rhs))?.num()
+            }),
+            Self::Num(_) => Err(Error::no_solution("humn isn't on either side of root")),
         }
     }
 }
-impl Add for Val {
-    type Output = Val;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        self.op(rhs, Op::Add, |a,b| a + b)
-    }
-}
-impl Sub for Val {
-    type Output = Val;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        self.op(rhs, Op::Sub, |a,b| a - b)
-    }
-}
-impl Mul for Val {
-    type Output = Val;
-
-    fn mul(self, rhs: Self) -> Self::Output {
-        self.op(rhs, Op::Mul, |a,b| a * b)
-    }
-}
-impl Div for Val {
-    type Output = Val;
-
-    fn div(self, rhs: Self) -> Self::Output {
-        self.op(rhs, Op::Div, |a,b| a / b)
-    }
-}
 
 #[derive(Copy, Clone, Debug)]
-enum Op { Add, Sub, Mul, Div, RDiv }
+pub enum Op { Add, Sub, Mul, Div, RDiv }
 
 impl Op {
     fn reverse_rhs(&self, rhs: i64, ops: &mut Vec<(Op, i64)>) {
@@ -123,103 +95,153 @@ impl Op {
             _ => unreachable!()
         }
     }
-    fn apply(&self, lhs: Val, rhs: Val) -> Val {
+    fn apply(&self, lhs: Val, rhs: Val) -> Result<Val> {
         match self {
-            Op::Add => { lhs + rhs}
-            Op::Sub => { lhs - rhs }
-            Op::Mul => { lhs * rhs }
-            Op::Div => { lhs / rhs }
-            Op::RDiv => { rhs / lhs }
+            Op::Add => { lhs.op(rhs, Op::Add, i64::checked_add) }
+            Op::Sub => { lhs.op(rhs, Op::Sub, i64::checked_sub) }
+            Op::Mul => { lhs.op(rhs, Op::Mul, i64::checked_mul) }
+            Op::Div => { lhs.op(rhs, Op::Div, i64::checked_div) }
+            Op::RDiv => { rhs.op(lhs, Op::Div, i64::checked_div) }
         }
     }
 }
-impl FromPair<Rule> for Op {
-    fn from_pair(pair: Pair<Rule>) -> Self {
+impl TryFromPair<'_, Rule> for Op {
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<Rule>) -> Result<Self, ParseError> {
         match pair.as_str() {
-            "+" => Op::Add,
-            "-" => Op::Sub,
-            "*" => Op::Mul,
-            "/" => Op::Div,
-            s => panic!("Bad op: {}", s),
+            "+" => Ok(Op::Add),
+            "-" => Ok(Op::Sub),
+            "*" => Ok(Op::Mul),
+            "/" => Ok(Op::Div),
+            s => Err(pair_error(&pair, format!("Bad op: {}", s))),
         }
     }
 }
 
-#[derive(Clone, Debug)]
-enum Job {
+#[derive(Clone, Debug, FromPair)]
+pub enum Job<'a> {
+    #[pair(rule = "int")]
     Value(Num),
-    Expr(String, Op, String)
-}
-impl FromPair<Rule> for Job {
-    fn from_pair(pair: Pair<Rule>) -> Self {
-        let inner = pair.into_inner().next().unwrap();
-        match inner.as_rule() {
-            Rule::num => {
-                Self::Value(inner.parse_into())
-            },
-            Rule::expr => {
-                let mut pairs = inner.into_inner();
-                Self::Expr(
-                    pairs.parse_next(),
-                    pairs.parse_next(),
-                    pairs.parse_next(),
-                )
-            }
-            r => panic!("Bad job: {:?}", r),
-        }
-    }
+    Expr(&'a str, Op, &'a str)
 }
 
-struct Interpreter {
-    monkeys: HashMap<String, Job>,
-    values: HashMap<String, Val>,
+// Names borrow from the input.
+type Monkeys<'a> = HashMap<&'a str, Job<'a>>;
+
+struct Interpreter<'a> {
+    monkeys: Monkeys<'a>,
+    values: HashMap<&'a str, Val>,
+    // Monkeys being worked out, to catch ones waiting on themselves.
+    pending: HashSet<&'a str>,
 }
 
-impl Interpreter {
-    fn new(monkeys: HashMap<String, Job>) -> Self {
-        Self { monkeys, values: Default::default() }
+impl<'a> Interpreter<'a> {
+    fn new(monkeys: Monkeys<'a>) -> Self {
+        Self { monkeys, values: Default::default(), pending: Default::default() }
     }
 
-    fn eval(&mut self, name: &str) -> Val {
+    fn eval(&mut self, name: &'a str) -> Result<Val> {
         if let Some(num) = self.values.get(name) {
-            num.clone()
-        } else {
-            let num = match self.monkeys.get(name).unwrap().clone() {
-                Job::Value(num) => { Val::Num(num) }
-                Job::Expr(a, op, b) => {
-                    op.apply(self.eval(&a), self.eval(&b))
-                }
-            };
-            self.values.insert(name.into(), num.clone());
-            num
+            return Ok(num.clone());
         }
+        if !self.pending.insert(name) {
+            return Err(Error::no_solution(format!("monkey {} waits for itself", name)));
+        }
+        let job = self.monkeys.get(name).cloned()
+            .ok_or_else(|| Error::no_solution(format!("there is no monkey named {}", name)))?;
+        let num = match job {
+            Job::Value(num) => { Val::Num(num) }
+            Job::Expr(a, op, b) => {
+                op.apply(self.eval(a)?, self.eval(b)?)?
+            }
+        };
+        self.pending.remove(name);
+        self.values.insert(name, num.clone());
+        Ok(num)
     }
 }
 
-fn parse_input() -> HashMap<String,Job> {
-    let (input,) : (Vec<(String,Job)>,) =
-        InputParser::parse_file(Rule::input, "inputs/day21/input.txt");
-    HashMap::from_iter(input.into_iter())
+// The first monkey found waiting, directly or not, for itself.
+fn find_cycle<'a>(monkeys: &Monkeys<'a>, names: &[&'a str]) -> Option<&'a str> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Mark { Visiting, Done }
+
+    fn visit<'a>(name: &'a str, monkeys: &Monkeys<'a>, marks: &mut HashMap<&'a str, Mark>) -> Option<&'a str> {
+        match marks.get(name) {
+            Some(Mark::Visiting) => return Some(name),
+            Some(Mark::Done) => return None,
+            None => {}
+        }
+        marks.insert(name, Mark::Visiting);
+        if let Some(Job::Expr(a, _, b)) = monkeys.get(name) {
+            for arg in [*a, *b] {
+                if let Some(cycle) = visit(arg, monkeys, marks) {
+                    return Some(cycle);
+                }
+            }
+        }
+        marks.insert(name, Mark::Done);
+        None
+    }
+
+    let mut marks = HashMap::new();
+    names.iter().find_map(|name| visit(name, monkeys, &mut marks))
 }
 
-pub fn part1() {
-    let monkeys = parse_input();
-    let mut interp = Interpreter::new(monkeys);
-    println!("{:?}", interp.eval("root"));
+fn parse_input(input: &str) -> Result<Monkeys<'_>> {
+    let (monkeys,) = InputParser::parse_str(Rule::input, input)?;
+    Ok(monkeys)
 }
 
-pub fn part2() {
-    let monkeys = parse_input();
-    if let Job::Expr(left, _, right) = monkeys.get("root").unwrap().clone() {
-        let mut interp = Interpreter::new(monkeys);
-        interp.values.insert("humn".into(), Val::Var(Vec::new()));
-        match (interp.eval(&left), interp.eval(&right)) {
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input<'a> = Monkeys<'a>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Monkeys<'a>> {
+        parse_input(input)
+    }
+
+    fn validate(&self, monkeys: &Monkeys<'_>, validator: &mut InputValidator) {
+        for name in ["root", "humn"] {
+            validator.check(monkeys.contains_key(name), || format!("there is no monkey named {}", name));
+        }
+        let mut names : Vec<&str> = monkeys.keys().copied().collect();
+        names.sort();
+        for name in &names {
+            if let Job::Expr(a, _, b) = &monkeys[name] {
+                for arg in [a, b].into_iter().filter(|arg| !monkeys.contains_key(*arg)) {
+                    validator.problem(format!("monkey {} waits for unknown monkey {}", name, arg));
+                }
+            }
+        }
+        if let Some(name) = find_cycle(monkeys, &names) {
+            validator.problem(format!("monkey {} waits for itself", name));
+        }
+    }
+
+    fn part1(&self, monkeys: &Monkeys<'_>) -> Result<Answer> {
+        let mut interp = Interpreter::new(monkeys.clone());
+        Ok(interp.eval("root")?.num()?.into())
+    }
+
+    fn part2(&self, monkeys: &Monkeys<'_>) -> Result<Answer> {
+        let Some(Job::Expr(left, _, right)) = monkeys.get("root").cloned() else {
+            return Err(Error::no_solution("root is not an expression"));
+        };
+        let mut interp = Interpreter::new(monkeys.clone());
+        interp.values.insert("humn", Val::Var(Vec::new()));
+        match (interp.eval(left)?, interp.eval(right)?) {
             (var@ Val::Var(_), num @ Val::Num(_)) |
             (num @ Val::Num(_), var @ Val::Var(_)) => {
-                println!("{}", var.solve(num.num()).unwrap())
+                Ok(var.solve(num.num()?)?.into())
             }
-            _ => unreachable!()
+            _ => Err(Error::no_solution("humn must appear on exactly one side of root"))
         }
+    }
 
+    fn example_answers(&self) -> [Answer; 2] {
+        [152.into(), 301.into()]
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,21 @@
 use std::collections::HashSet;
-use crate::util::read_lines;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::parsing::lines::{lines, Line};
+use crate::solution::Solution;
 
-struct Rucksack {
+pub struct Rucksack {
     chars: Vec<char>,
 }
 
 impl Rucksack {
-    fn from_line(line: &str) -> Rucksack {
-        Rucksack {
-            chars: line.chars().collect(),
+    fn from_line(line: Line) -> Result<Rucksack, ParseError> {
+        if let Some(col) = line.text.chars().position(|c| !c.is_ascii_alphabetic()) {
+            return Err(line.error(col + 1, "items must be letters"));
         }
+        Ok(Rucksack {
+            chars: line.text.chars().collect(),
+        })
     }
 
     fn items(&self) -> HashSet<char> {
@@ -28,39 +34,39 @@ impl Rucksack {
     }
 }
 
-struct Day3 {
+pub struct Rucksacks {
     score_order: Vec<char>,
     rucksacks: Vec<Rucksack>,
 }
 
-impl Day3 {
-   fn new(filename: &str) -> Self {
+impl Rucksacks {
+   fn new(input: &str) -> Result<Self, ParseError> {
        let mut score_order : Vec<_> = ('a'..='z').collect();
        score_order.extend('A'..='Z');
-       Self {
-           score_order: score_order,
-           rucksacks: read_rucksacks(filename),
-       }
+       Ok(Self {
+           score_order,
+           rucksacks: read_rucksacks(input)?,
+       })
    }
 
-    fn part1(&self) -> usize {
+    fn part1(&self) -> Result<usize> {
         let mut score = 0;
-        for rucksack in &self.rucksacks {
-            score += self.score_item(common_item(rucksack.compartment_items().iter().cloned()));
+        for (i, rucksack) in self.rucksacks.iter().enumerate() {
+            let item = common_item(rucksack.compartment_items().iter().cloned())
+                .ok_or_else(|| Error::no_solution(format!("no common item in rucksack {}", i + 1)))?;
+            score += self.score_item(item);
         }
-        score
+        Ok(score)
     }
 
-    fn part2(&self) -> usize {
+    fn part2(&self) -> Result<usize> {
         let mut score = 0;
-        for group in self.rucksacks.chunks(3) {
-            let common_item = *group.iter()
-                .map(|r| r.items())
-                .reduce(|a,b| a.intersection(&b).cloned().collect()).unwrap()
-                .iter().next().unwrap();
-            score += self.score_item(common_item);
+        for (i, group) in self.rucksacks.chunks(3).enumerate() {
+            let item = common_item(group.iter().map(|r| r.items()))
+                .ok_or_else(|| Error::no_solution(format!("no common item in group {}", i + 1)))?;
+            score += self.score_item(item);
         }
-        score
+        Ok(score)
     }
 
     fn score_item(&self, item: char) -> usize {
@@ -68,27 +74,39 @@ impl Day3 {
     }
 }
 
-fn common_item<I>(items: I) -> char
+fn common_item<I>(items: I) -> Option<char>
     where
     I: Iterator<Item = HashSet<char>>
 {
-    *items
-        .reduce(|a,b| a.intersection(&b).cloned().collect()).unwrap()
-        .iter().next().unwrap()
+    items
+        .reduce(|a,b| a.intersection(&b).cloned().collect())?
+        .iter().next().copied()
 }
 
 
-fn read_rucksacks(filename: &str) -> Vec<Rucksack> {
-    read_lines(filename).iter().map(|line| Rucksack::from_line(line)).collect()
+fn read_rucksacks(input: &str) -> Result<Vec<Rucksack>, ParseError> {
+    lines(input).map(Rucksack::from_line).collect()
 }
 
 
-pub fn part1() {
-    let day3 = Day3::new("inputs/day3/part1.txt");
-    println!("{}", day3.part1());
-}
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input<'a> = Rucksacks;
+
+    fn parse(&self, input: &str) -> Result<Rucksacks> {
+        Ok(Rucksacks::new(input)?)
+    }
+
+    fn part1(&self, rucksacks: &Rucksacks) -> Result<Answer> {
+        Ok(rucksacks.part1()?.into())
+    }
+
+    fn part2(&self, rucksacks: &Rucksacks) -> Result<Answer> {
+        Ok(rucksacks.part2()?.into())
+    }
 
-pub fn part2() {
-    let day3 = Day3::new("inputs/day3/part2.txt");
-    println!("{}", day3.part2());
-}
\ No newline at end of file
+    fn example_answers(&self) -> [Answer; 2] {
+        [157.into(), 70.into()]
+    }
+}
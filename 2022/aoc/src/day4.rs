@@ -1,16 +1,16 @@
 use std::ops::Range;
-use crate::util::read_lines;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::parsing::lines::lines;
+use crate::solution::Solution;
 
 type Section = Range<i32>;
 type Pair = (Section, Section);
 
-fn read_pairs(filename: &str) -> Vec<Pair> {
-    read_lines(filename).iter().map(|line| {
-        let sections : Vec<Section>= line.split(",").map(|section| {
-            let parts = section.split("-").take(2).map(|str| str.parse().unwrap()).collect::<Vec<i32>>();
-            Range { start: parts[0], end: parts[1] }
-        }).collect();
-        (sections[0].clone(), sections[1].clone())
+fn read_pairs(input: &str) -> Result<Vec<Pair>, ParseError> {
+    lines(input).map(|line| {
+        let [a, b, c, d] = line.scan("{}-{},{}-{}")?;
+        Ok((a..b, c..d))
     }).collect()
 }
 
@@ -19,16 +19,27 @@ fn fully_contains(a: &Section, b: &Section) -> bool {
 }
 
 fn overlaps(a: &Section, b: &Section) -> bool {
-    (b.start <= a.end && !(b.end < a.start)) ||
-        (a.start <= b.end && !(a.end < b.start))
+    b.start <= a.end && a.start <= b.end
 }
 
-pub fn part1() {
-    let score = read_pairs("inputs/day4/part1.txt").iter().filter(|(a,b)| fully_contains(a,b)).count();
-    println!("{}", score);
-}
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input<'a> = Vec<Pair>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Pair>> {
+        Ok(read_pairs(input)?)
+    }
 
-pub fn part2() {
-    let score = read_pairs("inputs/day4/part2.txt").iter().filter(|(a,b)| overlaps(a,b)).count();
-    println!("{}", score);
-}
\ No newline at end of file
+    fn part1(&self, pairs: &Vec<Pair>) -> Result<Answer> {
+        Ok(pairs.iter().filter(|(a,b)| fully_contains(a,b)).count().into())
+    }
+
+    fn part2(&self, pairs: &Vec<Pair>) -> Result<Answer> {
+        Ok(pairs.iter().filter(|(a,b)| overlaps(a,b)).count().into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [2.into(), 4.into()]
+    }
+}
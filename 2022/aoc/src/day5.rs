@@ -1,30 +1,43 @@
-use std::fs::read_to_string;
-
 use pest::iterators::Pair;
 use pest::Parser;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::parsing::{pair_error, pest_grammar};
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar = "src/day5.pest"]
+#[pest_grammar("src/day5.pest")]
 struct Day5Parser;
 
 #[derive(Copy, Clone, Debug)]
-struct Instruction {
+pub struct Instruction {
     pub count: usize,
     pub from: usize,
     pub to: usize,
 }
 
 impl Instruction {
-    fn apply(&self, state: &mut State) {
+    fn check(&self, state: &State) -> Result<()> {
+        if state[self.from].len() < self.count {
+            return Err(Error::no_solution(format!("can't move {} crates from stack {}", self.count, self.from + 1)));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, state: &mut State) -> Result<()> {
+        self.check(state)?;
         for _ in 0..self.count {
             let value = state[self.from].pop().unwrap();
             state[self.to].push(value);
         }
+        Ok(())
     }
-    fn apply_grouped(&self, state: &mut State) {
+
+    fn apply_grouped(&self, state: &mut State) -> Result<()> {
+        self.check(state)?;
         let from_len = state[self.from].len();
         let mut values : Vec<_> = state[self.from].drain((from_len-self.count)..from_len).collect();
         state[self.to].append(&mut values);
+        Ok(())
     }
 }
 
@@ -46,48 +59,67 @@ fn parse_initial_state(initial_state: Pair<Rule>) -> State {
     state
 }
 
-fn parse_instructions(instructions: Pair<Rule>) -> Vec<Instruction> {
+fn parse_stack(label: Pair<Rule>, num_stacks: usize) -> Result<usize, ParseError> {
+    match label.as_str().parse::<usize>() {
+        Ok(n) if (1..=num_stacks).contains(&n) => Ok(n - 1),
+        _ => Err(pair_error(&label, format!("there is no stack {}", label.as_str()))),
+    }
+}
+
+fn parse_instructions(instructions: Pair<Rule>, num_stacks: usize) -> Result<Vec<Instruction>, ParseError> {
     let instruction_list = instructions.into_inner();
     instruction_list.map(|instruction| {
         let mut parts = instruction.into_inner();
-        Instruction {
-            count: parts.next().unwrap().as_str().parse().unwrap(),
-            from: parts.next().unwrap().as_str().parse::<usize>().unwrap() - 1,
-            to: parts.next().unwrap().as_str().parse::<usize>().unwrap() - 1,
-        }
+        let count = parts.next().unwrap();
+        Ok(Instruction {
+            count: count.as_str().parse().map_err(|_| pair_error(&count, "count is too large"))?,
+            from: parse_stack(parts.next().unwrap(), num_stacks)?,
+            to: parse_stack(parts.next().unwrap(), num_stacks)?,
+        })
     }).collect()
 }
 
-fn parse_input(text: &str) -> Option<Input> {
-    match Day5Parser::parse(Rule::day5, &text) {
-        Ok(mut pairs) => {
-            let mut day5 = pairs.next().unwrap().into_inner();
-            let initial_state = parse_initial_state(day5.next().unwrap());
-            let instructions = parse_instructions(day5.next().unwrap());
-            Some( (initial_state, instructions) )
-        }
-        Err(err) => {
-            println!("Error parsing input: {}", err);
-            None
-        }
-    }
+fn parse_input(text: &str) -> Result<Input> {
+    let mut pairs = Day5Parser::parse(Rule::day5, text)?;
+    let mut day5 = pairs.next().unwrap().into_inner();
+    let initial_state = parse_initial_state(day5.next().unwrap());
+    let instructions = parse_instructions(day5.next().unwrap(), initial_state.len())?;
+    Ok( (initial_state, instructions) )
 }
 
-pub fn part1() {
-    let input = read_to_string("inputs/day5/input.txt").unwrap();
-    if let Some((mut state, instructions)) = parse_input(&input) {
+fn top_crates(state: &State) -> Result<Answer> {
+    state.iter().enumerate()
+        .map(|(i, s)| s.last().ok_or_else(|| Error::no_solution(format!("stack {} ends up empty", i + 1))))
+        .collect::<Result<String>>()
+        .map(Answer::from)
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input<'a> = Input;
+
+    fn parse(&self, input: &str) -> Result<Input> {
+        parse_input(input)
+    }
+
+    fn part1(&self, (state, instructions): &Input) -> Result<Answer> {
+        let mut state = state.clone();
         for inst in instructions {
-            inst.apply(&mut state);
+            inst.apply(&mut state)?;
         }
-        println!("{}", state.iter().map(|s| s.iter().last().unwrap() ).collect::<String>());
+        top_crates(&state)
     }
-}
-pub fn part2() {
-    let input = read_to_string("inputs/day5/input.txt").unwrap();
-    if let Some((mut state, instructions)) = parse_input(&input) {
+
+    fn part2(&self, (state, instructions): &Input) -> Result<Answer> {
+        let mut state = state.clone();
         for inst in instructions {
-            inst.apply_grouped(&mut state);
+            inst.apply_grouped(&mut state)?;
         }
-        println!("{}", state.iter().map(|s| s.iter().last().unwrap() ).collect::<String>());
+        top_crates(&state)
     }
-}
\ No newline at end of file
+
+    fn example_answers(&self) -> [Answer; 2] {
+        ["CMZ".into(), "MCD".into()]
+    }
+}
@@ -1,14 +1,15 @@
-use std::fs::read_to_string;
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use crate::solution::Solution;
 
-fn has_duplicates(vec: &Vec<char>) -> bool {
-    let mut copy = vec.clone();
+fn has_duplicates(vec: &[char]) -> bool {
+    let mut copy = vec.to_vec();
     copy.sort();
     copy.dedup();
     copy.len() != vec.len()
 }
 
-fn find_sequence_start(filename: &str, min_length: usize) -> Option<usize> {
-    let input = read_to_string(filename).unwrap();
+fn find_sequence_start(input: &str, min_length: usize) -> Option<usize> {
     let mut buf : Vec<char> = Vec::new();
     for (i, char) in input.chars().enumerate() {
         buf.push(char);
@@ -22,10 +23,26 @@ fn find_sequence_start(filename: &str, min_length: usize) -> Option<usize> {
     None
 }
 
-pub fn part1() {
-    println!("{}", find_sequence_start("inputs/day6/input.txt", 4).unwrap());
-}
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input<'a> = String;
+
+    fn parse(&self, input: &str) -> Result<String> {
+        Ok(input.to_string())
+    }
+
+    fn part1(&self, input: &String) -> Result<Answer> {
+        let start = find_sequence_start(input, 4).ok_or(Error::no_solution("no start-of-packet marker"))?;
+        Ok(start.into())
+    }
+
+    fn part2(&self, input: &String) -> Result<Answer> {
+        let start = find_sequence_start(input, 14).ok_or(Error::no_solution("no start-of-message marker"))?;
+        Ok(start.into())
+    }
 
-pub fn part2() {
-    println!("{}", find_sequence_start("inputs/day6/input.txt", 14).unwrap());
-}
\ No newline at end of file
+    fn example_answers(&self) -> [Answer; 2] {
+        [7.into(), 19.into()]
+    }
+}
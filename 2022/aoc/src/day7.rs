@@ -1,49 +1,52 @@
 use std::collections::HashMap;
-use std::fs::read_to_string;
 use pest::iterators::Pair;
 use pest::Parser;
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::parsing::{pair_error, pest_grammar};
+use crate::solution::Solution;
 
-#[derive(Parser)]
-#[grammar = "src/day7.pest"]
+#[pest_grammar("src/day7.pest")]
 struct InputParser;
 
+// Directory names borrow from the input.
 #[derive(Default, Debug)]
-struct FileSystem {
-    dirs: HashMap<Vec<String>, usize>,
+pub struct FileSystem<'a> {
+    dirs: HashMap<Vec<&'a str>, usize>,
 }
 
-impl FileSystem {
+impl<'a> FileSystem<'a> {
     fn new() -> Self {
         Default::default()
     }
 
-    fn insert(&mut self, mut file_path: Vec<String>, size: usize) {
-        while file_path.len() > 0 {
+    fn insert(&mut self, mut file_path: Vec<&'a str>, size: usize) {
+        while !file_path.is_empty() {
             file_path.pop();
             *self.dirs.entry(file_path.clone()).or_insert(0) += size;
         }
     }
 }
 
-struct Cursor<'a> {
-    fs: &'a mut FileSystem,
-    path: Vec<String>,
+struct Cursor<'f, 'a> {
+    fs: &'f mut FileSystem<'a>,
+    path: Vec<&'a str>,
 }
 
-impl<'a> Cursor<'a> {
-    fn new(fs: &mut FileSystem) -> Cursor {
+impl<'f, 'a> Cursor<'f, 'a> {
+    fn new(fs: &'f mut FileSystem<'a>) -> Self {
         Cursor { fs, path: Vec::new() }
     }
 
-    fn add_file(&mut self, name: &str, size: usize) {
+    fn add_file(&mut self, name: &'a str, size: usize) {
         let mut file_vec = self.path.clone();
-        file_vec.push(name.into());
+        file_vec.push(name);
         self.fs.insert(file_vec, size);
     }
 
     fn pop_all(&mut self) { self.path.clear(); }
-    fn push(&mut self, dir: &str) {
-        self.path.push(dir.into());
+    fn push(&mut self, dir: &'a str) {
+        self.path.push(dir);
     }
 
     fn pop(&mut self) {
@@ -51,7 +54,7 @@ impl<'a> Cursor<'a> {
     }
 }
 
-fn parse_command(cursor: &mut Cursor, pair: Pair<Rule>) {
+fn parse_command<'a>(cursor: &mut Cursor<'_, 'a>, pair: Pair<'a, Rule>) -> Result<(), ParseError> {
    match pair.as_rule() {
        Rule::cd => {
            let dir = pair.into_inner().next().unwrap();
@@ -67,7 +70,8 @@ fn parse_command(cursor: &mut Cursor, pair: Pair<Rule>) {
                match entry.as_rule() {
                    Rule::file_entry => {
                        let mut pairs = entry.into_inner();
-                       let size : usize = pairs.next().unwrap().as_str().parse().unwrap();
+                       let size = pairs.next().unwrap();
+                       let size : usize = size.as_str().parse().map_err(|_| pair_error(&size, "file size is too large"))?;
                        let name  = pairs.next().unwrap().as_str();
                        cursor.add_file(name, size);
                    }
@@ -78,44 +82,49 @@ fn parse_command(cursor: &mut Cursor, pair: Pair<Rule>) {
        }
        _ => { unreachable!(); }
    }
+   Ok(())
 }
 
-fn parse_input(filename: &str) -> FileSystem {
+fn parse_input(input: &str) -> Result<FileSystem<'_>> {
     let mut fs = FileSystem::new();
     let mut cursor = Cursor::new(&mut fs);
 
-    let input = read_to_string(filename).unwrap();
-    match InputParser::parse(Rule::input, &input) {
-        Ok(mut pairs) => {
-            let commands = pairs.next().unwrap().into_inner().next().unwrap().into_inner();
-            for command in commands {
-                parse_command(&mut cursor, command);
-            }
-        }
-        Err(err) => {
-            println!("Error parsing input: {}", err);
-            panic!();
-        }
+    let mut pairs = InputParser::parse(Rule::input, input)?;
+    let commands = pairs.next().unwrap().into_inner().next().unwrap().into_inner();
+    for command in commands {
+        parse_command(&mut cursor, command)?;
     }
-    fs
+    Ok(fs)
 }
 
-pub fn part1() {
-    let fs = parse_input("inputs/day7/input.txt");
-    let sum : usize = fs.dirs.values()
-        .filter(| size| **size <= 100000 )
-        .sum();
+pub struct Day7;
 
-    println!("{}", sum);
-}
+impl Solution for Day7 {
+    type Input<'a> = FileSystem<'a>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<FileSystem<'a>> {
+        parse_input(input)
+    }
+
+    fn part1(&self, fs: &FileSystem<'_>) -> Result<Answer> {
+        let sum : usize = fs.dirs.values()
+            .filter(| size| **size <= 100000 )
+            .sum();
 
-pub fn part2() {
-    let fs = parse_input("inputs/day7/input.txt");
-    let total_size =fs.dirs[&Vec::new()];
-    let amount_needed = 30000000 + total_size - 70000000;
-    let mut sizes : Vec<_> = fs.dirs.values()
-        .filter(|size| **size >= amount_needed)
-        .collect();
-    sizes.sort();
-    println!("{}", sizes.first().unwrap());
-}
\ No newline at end of file
+        Ok(sum.into())
+    }
+
+    fn part2(&self, fs: &FileSystem<'_>) -> Result<Answer> {
+        let total_size = *fs.dirs.get(&Vec::new()).ok_or(Error::no_solution("the file system is empty"))?;
+        let amount_needed = (30000000 + total_size).saturating_sub(70000000);
+        let mut sizes : Vec<_> = fs.dirs.values()
+            .filter(|size| **size >= amount_needed)
+            .collect();
+        sizes.sort();
+        Ok((**sizes.first().unwrap()).into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [95437.into(), 24933642.into()]
+    }
+}
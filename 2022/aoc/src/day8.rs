@@ -1,29 +1,28 @@
-use crate::grid::{VecGrid, GridCursor, Grid};
-use crate::util::read_lines;
+use std::collections::HashSet;
+use crate::grid::{Direction, VecGrid, GridCursor, Grid};
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::solution::Solution;
 
 type Forest = VecGrid<u32>;
 
-fn read_input() -> Forest {
-    read_lines("inputs/day8/input.txt").iter()
-        .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
-        .collect::<Vec<Vec<u32>>>()
-        .into()
+fn read_input(input: &str) -> Result<Forest, ParseError> {
+    VecGrid::parse_chars(input, |c| c.to_digit(10).ok_or_else(|| format!("expected a tree height, found {:?}", c)))
 }
 
-fn is_visible_in_dir(cursor: GridCursor<Forest>, down: i32, right: i32) -> bool {
-    cursor.iter_by(down, right).all(|c| {
-        *c < *cursor
-    })
-}
-
-fn is_visible(cursor: GridCursor<Forest>) -> bool {
-    is_visible_in_dir(cursor.clone(), 1, 0)
-        || is_visible_in_dir(cursor.clone(), -1, 0)
-        || is_visible_in_dir(cursor.clone(), 0, 1)
-        || is_visible_in_dir(cursor.clone(), 0, -1)
+// Marks the trees seen looking along `line` from outside the forest.
+fn mark_visible<'a>(line: impl Iterator<Item = &'a GridCursor<'a, Forest>>, visible: &mut HashSet<(i32, i32)>) {
+    let mut tallest = None;
+    for c in line {
+        if tallest.is_none_or(|height| **c > height) {
+            visible.insert((c.row, c.col));
+            tallest = Some(**c);
+        }
+    }
 }
 
-fn visible_trees_in_dir(cursor: GridCursor<Forest>, down: i32, right: i32) -> usize {
+fn visible_trees_in_dir(cursor: GridCursor<Forest>, dir: Direction) -> usize {
+    let (down, right) = dir.delta();
     let mut visible_trees = 0;
     for c in cursor.iter_by(down, right) {
         if *c < *cursor {
@@ -32,24 +31,38 @@ fn visible_trees_in_dir(cursor: GridCursor<Forest>, down: i32, right: i32) -> us
             return visible_trees + 1;
         }
     }
-    return visible_trees;
+    visible_trees
 }
 
 fn scenic_score(cursor: GridCursor<Forest>) -> usize {
-    visible_trees_in_dir(cursor.clone(), 1, 0)
-        * visible_trees_in_dir(cursor.clone(), -1, 0)
-        * visible_trees_in_dir(cursor.clone(), 0, 1)
-        * visible_trees_in_dir(cursor.clone(), 0, -1)
+    Direction::ALL.iter().map(|dir| visible_trees_in_dir(cursor.clone(), *dir)).product()
 }
 
-pub fn part1() {
-    let grid = read_input();
-    let visible = grid.cursors().filter(|c| is_visible(c.clone())).count();
-    println!("{}", visible);
-}
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input<'a> = Forest;
+
+    fn parse(&self, input: &str) -> Result<Forest> {
+        Ok(read_input(input)?)
+    }
 
-pub fn part2() {
-    let grid = read_input();
-    let max_scenic_score = grid.cursors().map(|c| scenic_score(c.clone())).max().unwrap();
-    println!("{}", max_scenic_score);
-}
\ No newline at end of file
+    fn part1(&self, grid: &Forest) -> Result<Answer> {
+        let mut visible = HashSet::new();
+        for line in grid.rows().chain(grid.cols()) {
+            let line : Vec<_> = line.collect();
+            mark_visible(line.iter(), &mut visible);
+            mark_visible(line.iter().rev(), &mut visible);
+        }
+        Ok(visible.len().into())
+    }
+
+    fn part2(&self, grid: &Forest) -> Result<Answer> {
+        let best = grid.cursors().map(|c| scenic_score(c.clone())).max().ok_or(Error::no_solution("there are no trees"))?;
+        Ok(best.into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [21.into(), 8.into()]
+    }
+}
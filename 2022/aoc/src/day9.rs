@@ -1,10 +1,13 @@
 use std::collections::HashSet;
-use crate::util::read_lines;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::grid::Direction;
+use crate::parsing::lines::lines;
+use crate::solution::Solution;
 
 #[derive(Clone, Copy, Debug)]
-struct Instruction {
-    down: i32,
-    right: i32,
+pub struct Instruction {
+    dir: Direction,
     steps: u32,
 }
 
@@ -24,8 +27,9 @@ impl Rope {
 
     fn move_head(&mut self, inst: Instruction) {
         for _ in 0..inst.steps {
-            self.knots[0].0 += inst.down;
-            self.knots[0].1 += inst.right;
+            let (down, right) = inst.dir.delta();
+            self.knots[0].0 += down;
+            self.knots[0].1 += right;
             for i in 1..self.knots.len() {
                 let vec = (
                     self.knots[i-1].0 - self.knots[i].0,
@@ -41,36 +45,48 @@ impl Rope {
     }
 }
 
-fn read_input() -> Vec<Instruction> {
-    read_lines("inputs/day9/input.txt").iter()
+fn read_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    lines(input)
         .map(|line| {
-            let mut parts = line.split(" ");
-            let dir = parts.next().unwrap();
-            let steps = parts.next().unwrap().parse().unwrap();
-            match dir {
-                "R" => Instruction { down: 0, right: 1, steps },
-                "L" => Instruction { down: 0, right: -1, steps },
-                "D" => Instruction { down: 1, right: 0, steps },
-                "U" => Instruction { down: -1, right: 0, steps },
-                _ => { unreachable!(); }
-            }
+            let [dir, steps] = line.fields("{} {}")?;
+            let steps = line.parse_token(steps)?;
+            let dir = match dir {
+                "R" => Direction::Right,
+                "L" => Direction::Left,
+                "D" => Direction::Down,
+                "U" => Direction::Up,
+                _ => return Err(line.error_at(dir, format!("unknown direction {:?}", dir))),
+            };
+            Ok(Instruction { dir, steps })
         }).collect()
 }
 
-pub fn part1() {
-    let mut rope = Rope::new(2);
-    let instructions = read_input();
-    for instruction in instructions.iter() {
-        rope.move_head(*instruction);
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input<'a> = Vec<Instruction>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Instruction>> {
+        Ok(read_input(input)?)
     }
-    println!("{}", rope.seen.len());
-}
 
-pub fn part2() {
-    let mut rope = Rope::new(10);
-    let instructions = read_input();
-    for instruction in instructions.iter() {
-        rope.move_head(*instruction);
+    fn part1(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        let mut rope = Rope::new(2);
+        for instruction in instructions.iter() {
+            rope.move_head(*instruction);
+        }
+        Ok(rope.seen.len().into())
     }
-    println!("{}", rope.seen.len());
-}
\ No newline at end of file
+
+    fn part2(&self, instructions: &Vec<Instruction>) -> Result<Answer> {
+        let mut rope = Rope::new(10);
+        for instruction in instructions.iter() {
+            rope.move_head(*instruction);
+        }
+        Ok(rope.seen.len().into())
+    }
+
+    fn example_answers(&self) -> [Answer; 2] {
+        [13.into(), 1.into()]
+    }
+}
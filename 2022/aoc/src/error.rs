@@ -0,0 +1,127 @@
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use pest::error::{ErrorVariant, LineColLocation};
+use pest::RuleType;
+
+// Where in the input parsing failed. Lines and columns start at 1; the file
+// is filled in by whoever knows where the input came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    // Line and column just past the failing text, when it spans more than a
+    // position.
+    pub end: Option<(usize, usize)>,
+    // The grammar rule that failed to parse or convert, for pest inputs.
+    pub rule: Option<String>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self { file: None, line, column, end: None, rule: None, message: message.into() }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}: {}", file, self.line, self.column, self.message)?,
+            None => write!(f, "line {}, column {}: {}", self.line, self.column, self.message)?,
+        }
+        // pest's own messages already name the rules they expected.
+        let mut words = self.message.split(|c: char| !c.is_alphanumeric() && c != '_');
+        match &self.rule {
+            Some(rule) if !words.any(|word| word == rule) => write!(f, " (in {})", rule),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<R: RuleType> From<pest::error::Error<R>> for ParseError {
+    fn from(err: pest::error::Error<R>) -> Self {
+        let ((line, column), end) = match err.line_col {
+            LineColLocation::Pos(pos) => (pos, None),
+            LineColLocation::Span(start, end) => (start, Some(end)),
+        };
+        let rule = match &err.variant {
+            ErrorVariant::ParsingError { positives, negatives } => {
+                positives.first().or(negatives.first()).map(|rule| format!("{:?}", rule))
+            }
+            ErrorVariant::CustomError { .. } => None,
+        };
+        ParseError { file: None, line, column, end, rule, message: err.variant.message().into_owned() }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io { path: String, source: io::Error },
+    Parse(ParseError),
+    // The input parsed but breaks assumptions the day relies on; every
+    // problem found, not just the first.
+    InvalidInput { file: Option<String>, problems: Vec<String> },
+    // The input parsed but doesn't have an answer.
+    NoSolution(String),
+    InvalidPart(u32),
+}
+
+impl Error {
+    pub fn no_solution(message: impl Into<String>) -> Self {
+        Error::NoSolution(message.into())
+    }
+
+    // Names the file the input came from in parse errors.
+    pub fn in_file(self, file: impl Display) -> Self {
+        match self {
+            Error::Parse(err) => Error::Parse(ParseError { file: Some(file.to_string()), ..err }),
+            Error::InvalidInput { problems, .. } => Error::InvalidInput { file: Some(file.to_string()), problems },
+            err => err,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "Error reading {}: {}", path, source),
+            Error::Parse(err) => write!(f, "Parse error at {}", err),
+            Error::InvalidInput { file, problems } => {
+                match file {
+                    Some(file) => write!(f, "Invalid input in {}: ", file)?,
+                    None => write!(f, "Invalid input: ")?,
+                }
+                write!(f, "{}", problems.join("; "))
+            }
+            Error::NoSolution(message) => write!(f, "No solution: {}", message),
+            Error::InvalidPart(part) => write!(f, "There is no part {}", part),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl<R: RuleType> From<pest::error::Error<R>> for Error {
+    fn from(err: pest::error::Error<R>) -> Self {
+        Error::Parse(err.into())
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
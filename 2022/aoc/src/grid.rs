@@ -1,31 +1,186 @@
 use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Write};
-use std::ops::Deref;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use crate::error::ParseError;
+use crate::parsing::lines::try_char_grid;
 
+mod topology;
+mod view;
+
+pub use topology::{Flat, Stitched, Topology, Torus};
+pub use view::View;
+
+// Bounds are inclusive: every (row, col) with min_row <= row <= max_row
+// and min_col <= col <= max_col is in the grid. An empty grid has a max
+// below its min.
 pub trait Grid {
     type Item;
 
     fn get(&self, row: i32, col: i32) -> &Self::Item;
-    fn set(&mut self, row: i32, col: i32, val: Self::Item);
     fn min_row(&self) -> i32;
     fn max_row(&self) -> i32;
     fn min_col(&self) -> i32;
     fn max_col(&self) -> i32;
 
+    fn height(&self) -> usize {
+        span(self.min_row(), self.max_row())
+    }
+    fn width(&self) -> usize {
+        span(self.min_col(), self.max_col())
+    }
+    fn is_empty(&self) -> bool {
+        self.height() == 0 || self.width() == 0
+    }
+
     fn in_bounds(&self, row: i32, col: i32) -> bool {
         row >= self.min_row() && col >= self.min_col()
             && row <= self.max_row() && col <= self.max_col()
     }
 
-    fn cursors(&self) -> GridIterator<Self> {
-        GridIterator { cursor: self.cursor_at(self.min_row(),self.min_col()) }
+    // Every cell, row by row.
+    fn cursors(&self) -> GridIterator<'_, Self> {
+        GridIterator { grid: self, row: self.min_row(), col: self.min_col() }
+    }
+    // The cells met going from `from` towards `dir`, not counting `from`
+    // itself, which may be just outside the grid.
+    fn ray(&self, (row, col): (i32, i32), dir: impl Into<Offset>) -> GridDirIterator<'_, Self> {
+        let (down, right) = dir.into().delta();
+        GridDirIterator { grid: self, row, col, down, right }
+    }
+    // Row `row`, left to right.
+    fn row(&self, row: i32) -> GridDirIterator<'_, Self> {
+        self.ray((row, self.min_col() - 1), Direction::Right)
     }
-    fn cursor_at(&self, row: i32, col: i32) -> Option<GridCursor<Self>> {
+    // Column `col`, top to bottom.
+    fn col(&self, col: i32) -> GridDirIterator<'_, Self> {
+        self.ray((self.min_row() - 1, col), Direction::Down)
+    }
+    fn rows(&self) -> impl Iterator<Item = GridDirIterator<'_, Self>> {
+        (self.min_row()..=self.max_row()).map(|row| self.row(row))
+    }
+    fn cols(&self) -> impl Iterator<Item = GridDirIterator<'_, Self>> {
+        (self.min_col()..=self.max_col()).map(|col| self.col(col))
+    }
+    fn cursor_at(&self, row: i32, col: i32) -> Option<GridCursor<'_, Self>> {
         GridCursor::new(self, row, col)
     }
+
+    // Views that read through to this grid; see view.rs.
+    fn transposed(&self) -> View<&Self> {
+        View::new(self).transposed()
+    }
+    fn rotated_cw(&self) -> View<&Self> {
+        View::new(self).rotated_cw()
+    }
+    fn flipped_h(&self) -> View<&Self> {
+        View::new(self).flipped_h()
+    }
+    fn window(&self, min_row: i32, min_col: i32, max_row: i32, max_col: i32) -> View<&Self> {
+        View::new(self).window(min_row, min_col, max_row, max_col)
+    }
+}
+
+// Grids that can be written to, directly or through a View of a &mut.
+pub trait GridMut: Grid {
+    fn get_mut(&mut self, row: i32, col: i32) -> &mut Self::Item;
+    fn set(&mut self, row: i32, col: i32, val: Self::Item);
+
+    fn swap(&mut self, (row1, col1): (i32, i32), (row2, col2): (i32, i32)) where Self::Item: Clone {
+        let val = self.get(row1, col1).clone();
+        let val = mem::replace(self.get_mut(row2, col2), val);
+        self.set(row1, col1, val);
+    }
+
+    fn cursor_at_mut(&mut self, row: i32, col: i32) -> Option<GridCursorMut<'_, Self>> {
+        GridCursorMut::new(self, row, col)
+    }
+}
+
+// Number of values in min..=max; HashMapGrid starts out with min at
+// i32::MAX and max at i32::MIN.
+fn span(min: i32, max: i32) -> usize {
+    if max < min { 0 } else { (max as i64 - min as i64 + 1) as usize }
+}
+
+// The four ways along a row or column, clockwise from up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
 }
 
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    // (down, right), the order move_by takes them in.
+    pub fn delta(self) -> (i32, i32) {
+        Offset::from(self).delta()
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+// A step to any of the eight cells around one, clockwise from up. Turns
+// are a quarter turn, as for Direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Offset {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Offset {
+    pub const ALL: [Offset; 8] = [
+        Offset::Up, Offset::UpRight, Offset::Right, Offset::DownRight,
+        Offset::Down, Offset::DownLeft, Offset::Left, Offset::UpLeft,
+    ];
+
+    pub fn delta(self) -> (i32, i32) {
+        NEIGHBORS8[self as usize]
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 2) % 8]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 6) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+}
+
+impl From<Direction> for Offset {
+    fn from(dir: Direction) -> Self {
+        Offset::ALL[dir as usize * 2]
+    }
+}
+
+// (down, right) steps, clockwise from up.
+const NEIGHBORS4 : [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const NEIGHBORS8 : [(i32, i32); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+const DIAGONALS : [(i32, i32); 4] = [(-1, 1), (1, 1), (1, -1), (-1, -1)];
 
 #[derive(PartialEq, Eq, Hash)]
 pub struct GridCursor<'a, G: Grid + ?Sized> {
@@ -61,8 +216,28 @@ impl<'a, G: Grid + ?Sized> GridCursor<'a, G>
         Self::new(self.grid, self.row + down, self.col + right)
     }
     pub fn iter_by(&self, down: i32, right: i32) -> GridDirIterator<'a, G> {
-        GridDirIterator { cursor: (*self).clone(), down, right }
+        GridDirIterator { grid: self.grid, row: self.row, col: self.col, down, right }
+    }
+    pub fn step(&self, offset: impl Into<Offset>) -> Option<Self> {
+        let (down, right) = offset.into().delta();
+        self.move_by(down, right)
     }
+    // The cells at each of `offsets` that are in the grid.
+    pub fn neighbors_with<'s>(&self, offsets: &'s [(i32, i32)]) -> impl Iterator<Item = Self> + 's
+        where 'a: 's
+    {
+        let cursor = self.clone();
+        offsets.iter().filter_map(move |&(down, right)| cursor.move_by(down, right))
+    }
+    // A step on `topology` rather than the flat grid, giving the way it's
+    // heading afterwards too.
+    pub fn walk(&self, topology: &impl Topology, dir: Direction) -> Option<(Self, Direction)> {
+        let ((row, col), dir) = topology.step(self.grid, (self.row, self.col), dir)?;
+        Some((Self::new(self.grid, row, col)?, dir))
+    }
+    pub fn neighbors4(&self) -> impl Iterator<Item = Self> { self.neighbors_with(&NEIGHBORS4) }
+    pub fn neighbors8(&self) -> impl Iterator<Item = Self> { self.neighbors_with(&NEIGHBORS8) }
+    pub fn diagonals(&self) -> impl Iterator<Item = Self> { self.neighbors_with(&DIAGONALS) }
     pub fn right(&self) -> Option<Self> { self.move_by(0, 1) }
     pub fn left(&self) -> Option<Self> { self.move_by(0, -1) }
     pub fn up(&self) -> Option<Self> { self.move_by(-1, 0) }
@@ -84,36 +259,118 @@ impl<'a, G> Debug for GridCursor<'a,G>
     }
 }
 
+// A cursor that can write to its cell, and moves itself rather than
+// giving out a new cursor, as only one can borrow the grid at a time.
+pub struct GridCursorMut<'a, G: GridMut + ?Sized> {
+    grid: &'a mut G,
+    pub row: i32,
+    pub col: i32,
+}
+
+impl<'a, G: GridMut + ?Sized> Deref for GridCursorMut<'a, G> {
+    type Target = G::Item;
+
+    fn deref(&self) -> &Self::Target {
+        self.grid.get(self.row, self.col)
+    }
+}
+
+impl<'a, G: GridMut + ?Sized> DerefMut for GridCursorMut<'a, G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.grid.get_mut(self.row, self.col)
+    }
+}
+
+impl<'a, G: GridMut + ?Sized> GridCursorMut<'a, G> {
+    fn new(grid: &'a mut G, row: i32, col: i32) -> Option<Self> {
+        if grid.in_bounds(row, col) {
+            Some(Self { grid, row, col })
+        } else {
+            None
+        }
+    }
+    // A read-only cursor at the same cell, for looking around.
+    pub fn as_cursor(&self) -> GridCursor<'_, G> {
+        GridCursor { grid: self.grid, row: self.row, col: self.col }
+    }
+    // Moves if the new cell is in the grid, returning whether it did.
+    pub fn move_by(&mut self, down: i32, right: i32) -> bool {
+        let moved = self.grid.in_bounds(self.row + down, self.col + right);
+        if moved {
+            self.row += down;
+            self.col += right;
+        }
+        moved
+    }
+    pub fn step(&mut self, offset: impl Into<Offset>) -> bool {
+        let (down, right) = offset.into().delta();
+        self.move_by(down, right)
+    }
+    // Steps on `topology`, giving the way it's heading afterwards, or None
+    // and staying put if the step leaves the grid.
+    pub fn walk(&mut self, topology: &impl Topology, dir: Direction) -> Option<Direction> {
+        let ((row, col), dir) = topology.step(&*self.grid, (self.row, self.col), dir)?;
+        self.grid.in_bounds(row, col).then(|| {
+            (self.row, self.col) = (row, col);
+            dir
+        })
+    }
+    // The value of the cell at `offset` from this one, if it's in the grid.
+    pub fn neighbor(&self, offset: impl Into<Offset>) -> Option<&G::Item> {
+        let (down, right) = offset.into().delta();
+        let (row, col) = (self.row + down, self.col + right);
+        self.grid.in_bounds(row, col).then(|| self.grid.get(row, col))
+    }
+    // Swaps values with the cell at `offset`, staying put. Does nothing,
+    // returning false, if that cell isn't in the grid.
+    pub fn swap_with(&mut self, offset: impl Into<Offset>) -> bool where G::Item: Clone {
+        let (down, right) = offset.into().delta();
+        let (row, col) = (self.row + down, self.col + right);
+        let swapped = self.grid.in_bounds(row, col);
+        if swapped {
+            self.grid.swap((self.row, self.col), (row, col));
+        }
+        swapped
+    }
+}
+
+// The position it's at next; it's done once that's past the last row.
 pub struct GridIterator<'a, G: Grid + ?Sized> {
-    cursor: Option<GridCursor<'a, G>>,
+    grid: &'a G,
+    row: i32,
+    col: i32,
 }
 
-impl<'a, G: Grid> Iterator for GridIterator<'a, G> {
+impl<'a, G: Grid + ?Sized> Iterator for GridIterator<'a, G> {
     type Item = GridCursor<'a, G>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let cur = self.cursor.clone();
-        self.cursor = cur.clone().and_then(|c| c.right().or(
-            c.iter_by(0, -1).last().and_then(|s| s.down()))
-        );
-        cur
+        let cur = GridCursor::new(self.grid, self.row, self.col)?;
+        self.col += 1;
+        if self.col > self.grid.max_col() {
+            self.col = self.grid.min_col();
+            self.row += 1;
+        }
+        Some(cur)
     }
 }
 
+// The position it last yielded, or started from.
 pub struct GridDirIterator<'a, G: Grid + ?Sized> {
-    cursor: GridCursor<'a, G>,
+    grid: &'a G,
+    row: i32,
+    col: i32,
     down: i32,
     right: i32,
 }
 
-impl<'a, G: Grid> Iterator for GridDirIterator<'a, G> {
+impl<'a, G: Grid + ?Sized> Iterator for GridDirIterator<'a, G> {
     type Item = GridCursor<'a, G>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.cursor.move_by(self.down, self.right).map(|cursor| {
-            self.cursor = cursor.clone();
-            cursor
-        })
+        let cursor = GridCursor::new(self.grid, self.row + self.down, self.col + self.right)?;
+        (self.row, self.col) = (cursor.row, cursor.col);
+        Some(cursor)
     }
 }
 
@@ -129,18 +386,98 @@ impl<T> Grid for VecGrid<T> {
         &self.cells[row as usize][col as usize]
     }
 
+    fn min_row(&self) -> i32 { 0 }
+    fn max_row(&self) -> i32 { self.cells.len() as i32 - 1 }
+    fn min_col(&self) -> i32 { 0 }
+    fn max_col(&self) -> i32 { self.cells.first().map_or(0, Vec::len) as i32 - 1 }
+}
+
+impl<T> GridMut for VecGrid<T> {
+    fn get_mut(&mut self, row: i32, col: i32) -> &mut T {
+        &mut self.cells[row as usize][col as usize]
+    }
+
     fn set(&mut self, row: i32, col: i32, val: T) {
         self.cells[row as usize][col as usize] = val;
     }
+}
 
-    fn min_row(&self) -> i32 { 0 }
-    fn max_row(&self) -> i32 { self.cells.len() as i32 }
-    fn min_col(&self) -> i32 { 0 }
-    fn max_col(&self) -> i32 { self.cells[0].len() as i32 }
+impl<T> VecGrid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Self where T: Clone {
+        Self { cells: vec![vec![fill; cols]; rows] }
+    }
+
+    // One row per line of `text`, mapping each character. Panics if the
+    // lines have different lengths; parse_chars reports that as an error
+    // instead, for text that might be ragged.
+    pub fn from_str_with(text: &str, mut mapper: impl FnMut(char) -> T) -> Self {
+        let cells : Vec<Vec<T>> = text.lines()
+            .map(|line| line.chars().map(&mut mapper).collect())
+            .collect();
+        cells.into()
+    }
+}
+
+impl<T: Clone> VecGrid<T> {
+    // A copy of any grid, moved so its top left is at (0, 0).
+    pub fn from_grid<G: Grid<Item = T> + ?Sized>(grid: &G) -> Self {
+        let cells : Vec<Vec<T>> = (grid.min_row()..=grid.max_row())
+            .map(|row| (grid.min_col()..=grid.max_col()).map(|col| grid.get(row, col).clone()).collect())
+            .collect();
+        cells.into()
+    }
+
+    // Owned versions of the views, rearranging the grid in place.
+    pub fn transpose(&mut self) {
+        *self = Self::from_grid(&self.transposed());
+    }
+    pub fn rotate_cw(&mut self) {
+        *self = Self::from_grid(&self.rotated_cw());
+    }
+    pub fn flip_h(&mut self) {
+        *self = Self::from_grid(&self.flipped_h());
+    }
+    pub fn crop(&mut self, min_row: i32, min_col: i32, max_row: i32, max_col: i32) {
+        *self = Self::from_grid(&self.window(min_row, min_col, max_row, max_col));
+    }
+}
+
+// Where each marker character was found, as (row, col).
+pub type Markers = HashMap<char, Vec<(i32, i32)>>;
+
+impl<T> VecGrid<T> {
+    // One row per line of `text`. Errors from `mapper`, and ragged rows,
+    // are reported at their line and column.
+    pub fn parse_chars<E: Display>(text: &str, mapper: impl FnMut(char) -> Result<T, E>) -> Result<Self, ParseError> {
+        Ok(try_char_grid(text, mapper)?.into())
+    }
+
+    // Like parse_chars, also giving the (row, col) of every occurrence of
+    // each of `markers`, which `mapper` still sees.
+    pub fn parse_chars_with_markers<E: Display>(
+        text: &str,
+        markers: &[char],
+        mapper: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<(Self, Markers), ParseError> {
+        let grid = Self::parse_chars(text, mapper)?;
+        let mut found : Markers = markers.iter().map(|c| (*c, Vec::new())).collect();
+        for (row, line) in text.lines().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                if let Some(positions) = found.get_mut(&c) {
+                    positions.push((row as i32, col as i32));
+                }
+            }
+        }
+        Ok((grid, found))
+    }
 }
 
+// Panics if the rows have different lengths.
 impl<T> From<Vec<Vec<T>>> for VecGrid<T> {
     fn from(cells: Vec<Vec<T>>) -> Self {
+        if let Some(first) = cells.first() {
+            assert!(cells.iter().all(|row| row.len() == first.len()), "grid rows have different lengths");
+        }
         Self { cells }
     }
 }
@@ -168,6 +505,15 @@ impl<T> HashMapGrid<T> {
     }
 }
 
+impl<T> HashMapGrid<T> {
+    fn grow(&mut self, row: i32, col: i32) {
+        self.min_col = min(col, self.min_col);
+        self.min_row = min(row, self.min_row);
+        self.max_col = max(col, self.max_col);
+        self.max_row = max(row, self.max_row);
+    }
+}
+
 impl<T: Clone> Grid for HashMapGrid<T> {
     type Item = T;
 
@@ -175,20 +521,25 @@ impl<T: Clone> Grid for HashMapGrid<T> {
         self.cells.get(&(row, col)).unwrap_or(&self.default)
     }
 
-    fn set(&mut self, row: i32, col: i32, val: T) {
-        self.min_col = min(col, self.min_col);
-        self.min_row = min(row, self.min_row);
-        self.max_col = max(col, self.max_col);
-        self.max_row = max(row, self.max_row);
-        self.cells.insert((row, col), val);
-    }
-
     fn min_row(&self) -> i32 { self.min_row }
     fn max_row(&self) -> i32 { self.max_row }
     fn min_col(&self) -> i32 { self.min_col }
     fn max_col(&self) -> i32 { self.max_col }
 }
 
+impl<T: Clone> GridMut for HashMapGrid<T> {
+    // Like set, grows the bounds to take in (row, col).
+    fn get_mut(&mut self, row: i32, col: i32) -> &mut T {
+        self.grow(row, col);
+        self.cells.entry((row, col)).or_insert_with(|| self.default.clone())
+    }
+
+    fn set(&mut self, row: i32, col: i32, val: T) {
+        self.grow(row, col);
+        self.cells.insert((row, col), val);
+    }
+}
+
 pub fn format_grid<G>(grid: &G, f: &mut Formatter<'_>) -> std::fmt::Result
 where G: Grid,
       G::Item: Display
@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+use super::{Direction, Grid};
+
+type Pos = (i32, i32);
+
+// How the cells of a grid join up: where a step from `pos` heading `dir`
+// lands, and which way it's heading once it's there. None if the step
+// leaves the grid.
+pub trait Topology {
+    fn step<G: Grid + ?Sized>(&self, grid: &G, pos: Pos, dir: Direction) -> Option<(Pos, Direction)>;
+}
+
+// Hard edges, as everywhere else in the grid code.
+pub struct Flat;
+
+impl Topology for Flat {
+    fn step<G: Grid + ?Sized>(&self, grid: &G, (row, col): Pos, dir: Direction) -> Option<(Pos, Direction)> {
+        let (down, right) = dir.delta();
+        let (row, col) = (row + down, col + right);
+        grid.in_bounds(row, col).then_some(((row, col), dir))
+    }
+}
+
+// Going off one edge comes back in at the opposite one. Wrapping inside
+// walls is a Torus on a window of the grid.
+pub struct Torus;
+
+impl Topology for Torus {
+    fn step<G: Grid + ?Sized>(&self, grid: &G, (row, col): Pos, dir: Direction) -> Option<(Pos, Direction)> {
+        if grid.is_empty() {
+            return None;
+        }
+        let (down, right) = dir.delta();
+        let row = wrap(row + down, grid.min_row(), grid.height());
+        let col = wrap(col + right, grid.min_col(), grid.width());
+        Some(((row, col), dir))
+    }
+}
+
+fn wrap(x: i32, min: i32, len: usize) -> i32 {
+    min + (x - min).rem_euclid(len as i32)
+}
+
+// Flat, except for the steps stitched to land somewhere else.
+#[derive(Clone, Debug, Default)]
+pub struct Stitched {
+    stitches: HashMap<(Pos, Direction), (Pos, Direction)>,
+}
+
+impl Topology for Stitched {
+    fn step<G: Grid + ?Sized>(&self, grid: &G, pos: Pos, dir: Direction) -> Option<(Pos, Direction)> {
+        match self.stitches.get(&(pos, dir)) {
+            Some(&to) => Some(to),
+            None => Flat.step(grid, pos, dir),
+        }
+    }
+}
+
+impl Stitched {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Stepping from `from` heading `dir` lands on `to` heading `to_dir`,
+    // and turning round there leads back.
+    pub fn stitch(&mut self, from: Pos, dir: Direction, to: Pos, to_dir: Direction) {
+        self.stitches.insert((from, dir), (to, to_dir));
+        self.stitches.insert((to, to_dir.opposite()), (from, dir.opposite()));
+    }
+
+    // Folds the net of a cube drawn on `grid`, where `on_face` tells the
+    // cells of the six faces from the gaps around them. Walking off a face
+    // carries on over the edge it's folded against, onto whichever face
+    // that is, turned to match.
+    pub fn fold_cube<G: Grid + ?Sized>(grid: &G, on_face: impl Fn(&G::Item) -> bool) -> Result<Self, String> {
+        let cells = grid.cursors().filter(|c| on_face(grid.get(c.row, c.col))).count();
+        let size = (1..).find(|size| 6 * size * size >= cells).unwrap();
+        if cells == 0 || 6 * size * size != cells {
+            return Err(format!("{} cells can't make six square faces", cells));
+        }
+
+        let size = size as i32;
+        let is_face = |(row, col): Pos| grid.in_bounds(row, col) && on_face(grid.get(row, col));
+        let corners : Vec<Pos> = (grid.min_row()..=grid.max_row()).step_by(size as usize)
+            .flat_map(|row| (grid.min_col()..=grid.max_col()).step_by(size as usize).map(move |col| (row, col)))
+            .filter(|&corner| is_face(corner))
+            .collect();
+        if corners.len() != 6 {
+            return Err(format!("found {} faces of size {}, not 6", corners.len(), size));
+        }
+
+        // Roll the cube over the net, face by face.
+        let mut frames : HashMap<Pos, Frame> = HashMap::new();
+        let mut queue = VecDeque::from([(corners[0], Frame::START)]);
+        while let Some((corner, frame)) = queue.pop_front() {
+            if frames.contains_key(&corner) {
+                continue;
+            }
+            frames.insert(corner, frame);
+            for dir in Direction::ALL {
+                let (down, right) = dir.delta();
+                let next = (corner.0 + down * size, corner.1 + right * size);
+                if corners.contains(&next) && !frames.contains_key(&next) {
+                    queue.push_back((next, frame.roll(dir)));
+                }
+            }
+        }
+        if frames.len() != 6 {
+            return Err("the faces aren't joined up".into());
+        }
+        let faces : HashMap<[i32; 3], (Pos, Frame)> = frames.iter().map(|(&corner, &frame)| (frame.normal, (corner, frame))).collect();
+        if faces.len() != 6 {
+            return Err("faces overlap when folded".into());
+        }
+
+        // Edges are numbered clockwise round each face, so a shared edge
+        // runs opposite ways on its two faces.
+        let mut stitched = Stitched::new();
+        for &(corner, frame) in faces.values() {
+            for dir in Direction::ALL {
+                let (other_corner, other_frame) = faces[&frame.edge(dir)];
+                let other_dir = Direction::ALL.into_iter().find(|d| other_frame.edge(*d) == frame.normal).unwrap();
+                for i in 0..size {
+                    let from = edge_cell(corner, size, dir, i);
+                    let to = edge_cell(other_corner, size, other_dir, size - 1 - i);
+                    stitched.stitches.insert((from, dir), (to, other_dir.opposite()));
+                }
+            }
+        }
+        Ok(stitched)
+    }
+}
+
+// The `i`th cell, clockwise, along the `dir` edge of the face with its top
+// left at `corner`.
+fn edge_cell((row, col): Pos, size: i32, dir: Direction, i: i32) -> Pos {
+    let last = size - 1;
+    match dir {
+        Direction::Up => (row, col + i),
+        Direction::Right => (row + i, col + last),
+        Direction::Down => (row + last, col + last - i),
+        Direction::Left => (row + last - i, col),
+    }
+}
+
+// Which way a face's right and down point once the net is folded, and its
+// outward normal, as unit vectors.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    right: [i32; 3],
+    down: [i32; 3],
+    normal: [i32; 3],
+}
+
+fn neg(v: [i32; 3]) -> [i32; 3] {
+    v.map(|x| -x)
+}
+
+impl Frame {
+    const START : Frame = Frame { right: [1, 0, 0], down: [0, 1, 0], normal: [0, 0, 1] };
+
+    // The frame of the face next to this one in the net, towards `dir`.
+    fn roll(self, dir: Direction) -> Self {
+        let Frame { right, down, normal } = self;
+        match dir {
+            Direction::Up => Frame { right, down: normal, normal: neg(down) },
+            Direction::Right => Frame { right: neg(normal), down, normal: right },
+            Direction::Down => Frame { right, down: neg(normal), normal: down },
+            Direction::Left => Frame { right: normal, down, normal: neg(right) },
+        }
+    }
+
+    // Which way the edge on the `dir` side of the face is, from its centre;
+    // also the normal of the face folded against it.
+    fn edge(self, dir: Direction) -> [i32; 3] {
+        match dir {
+            Direction::Up => neg(self.down),
+            Direction::Right => self.right,
+            Direction::Down => self.down,
+            Direction::Left => neg(self.right),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use super::{format_grid, Grid, GridMut};
+
+// Another grid seen transposed, rotated, flipped or through a window,
+// without copying it. `R` is a reference to the grid: a view of a `&G` can
+// be read, and one of a `&mut G` written to as well. Transforming a view
+// gives another view of the same grid, not a view of a view.
+//
+// A view keeps the grid's bounds where it can: rotating or transposing
+// swaps the row and column ranges, and a window keeps the coordinates of
+// the cells it shows.
+pub struct View<R> {
+    grid: R,
+    // Where a view position (row, col) is in the grid:
+    // grid row = map[0][0] * row + map[0][1] * col + map[0][2], and so on.
+    map: [[i32; 3]; 2],
+    min_row: i32,
+    max_row: i32,
+    min_col: i32,
+    max_col: i32,
+}
+
+impl<R> View<R>
+    where
+        R: Deref,
+        R::Target: Grid
+{
+    pub fn new(grid: R) -> Self {
+        let (min_row, max_row, min_col, max_col) = (grid.min_row(), grid.max_row(), grid.min_col(), grid.max_col());
+        Self { grid, map: [[1, 0, 0], [0, 1, 0]], min_row, max_row, min_col, max_col }
+    }
+
+    // Follows `step`, from positions in the new view to positions in this
+    // one, with the map so far.
+    fn then(self, step: [[i32; 3]; 2], (min_row, max_row, min_col, max_col): (i32, i32, i32, i32)) -> Self {
+        let m = self.map;
+        let map = [0, 1].map(|i| [
+            m[i][0] * step[0][0] + m[i][1] * step[1][0],
+            m[i][0] * step[0][1] + m[i][1] * step[1][1],
+            m[i][0] * step[0][2] + m[i][1] * step[1][2] + m[i][2],
+        ]);
+        Self { map, min_row, max_row, min_col, max_col, ..self }
+    }
+
+    fn to_grid(&self, row: i32, col: i32) -> (i32, i32) {
+        let [r, c] = self.map.map(|m| m[0] * row + m[1] * col + m[2]);
+        (r, c)
+    }
+
+    pub fn transposed(self) -> Self {
+        let bounds = (self.min_col, self.max_col, self.min_row, self.max_row);
+        self.then([[0, 1, 0], [1, 0, 0]], bounds)
+    }
+
+    // The top row becomes the right hand column.
+    pub fn rotated_cw(self) -> Self {
+        let step = [[0, -1, self.min_row + self.max_row], [1, 0, 0]];
+        let bounds = (self.min_col, self.max_col, self.min_row, self.max_row);
+        self.then(step, bounds)
+    }
+
+    // Mirrored left to right.
+    pub fn flipped_h(self) -> Self {
+        let step = [[1, 0, 0], [0, -1, self.min_col + self.max_col]];
+        let bounds = (self.min_row, self.max_row, self.min_col, self.max_col);
+        self.then(step, bounds)
+    }
+
+    // The cells from (min_row, min_col) to (max_row, max_col) inclusive
+    // that are in the view.
+    pub fn window(self, min_row: i32, min_col: i32, max_row: i32, max_col: i32) -> Self {
+        let bounds = (
+            min_row.max(self.min_row), max_row.min(self.max_row),
+            min_col.max(self.min_col), max_col.min(self.max_col),
+        );
+        self.then([[1, 0, 0], [0, 1, 0]], bounds)
+    }
+}
+
+impl<R> Grid for View<R>
+    where
+        R: Deref,
+        R::Target: Grid
+{
+    type Item = <R::Target as Grid>::Item;
+
+    fn get(&self, row: i32, col: i32) -> &Self::Item {
+        let (row, col) = self.to_grid(row, col);
+        self.grid.get(row, col)
+    }
+
+    fn min_row(&self) -> i32 { self.min_row }
+    fn max_row(&self) -> i32 { self.max_row }
+    fn min_col(&self) -> i32 { self.min_col }
+    fn max_col(&self) -> i32 { self.max_col }
+}
+
+impl<R> GridMut for View<R>
+    where
+        R: DerefMut,
+        R::Target: GridMut
+{
+    fn get_mut(&mut self, row: i32, col: i32) -> &mut Self::Item {
+        let (row, col) = self.to_grid(row, col);
+        self.grid.get_mut(row, col)
+    }
+
+    fn set(&mut self, row: i32, col: i32, val: Self::Item) {
+        let (row, col) = self.to_grid(row, col);
+        self.grid.set(row, col, val);
+    }
+}
+
+impl<R> Display for View<R>
+    where
+        R: Deref,
+        R::Target: Grid,
+        <R::Target as Grid>::Item: Display
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        format_grid(self, f)
+    }
+}
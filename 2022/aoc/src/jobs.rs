@@ -0,0 +1,113 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use aoc::solution::Runner;
+use aoc::util::{input_hash, InputSource};
+use crate::report::{Failure, Row};
+
+pub struct Job<'a> {
+    pub solution: &'static dyn Runner,
+    pub year: u32,
+    pub day: u32,
+    pub part: u32,
+    pub input: &'a str,
+    pub source: &'a InputSource,
+    pub example: bool,
+}
+
+fn run_job(job: &Job) -> Row {
+    let run = catch_unwind(AssertUnwindSafe(|| job.solution.run_part(job.part, job.input, job.example)))
+        .map_err(|_| Failure::Panicked)
+        .and_then(|run| run.map_err(|err| Failure::Error(err.in_file(job.source))));
+    let expected = if job.example { job.solution.example_answer(job.part) } else { None };
+    Row {
+        year: job.year,
+        day: job.day,
+        part: job.part,
+        input_hash: input_hash(job.input),
+        run,
+        expected,
+    }
+}
+
+// Runs the jobs on up to `threads` worker threads, each taking the next
+// unstarted job, and returns the rows in job order.
+pub fn run_jobs(jobs: &[Job], threads: usize) -> Vec<Row> {
+    let next = AtomicUsize::new(0);
+    let rows = Mutex::new(jobs.iter().map(|_| None).collect::<Vec<Option<Row>>>());
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else { break };
+                    let row = run_job(job);
+                    rows.lock().unwrap()[index] = Some(row);
+                }
+            });
+        }
+    });
+    rows.into_inner().unwrap().into_iter().map(|row| row.unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use aoc::answer::Answer;
+    use aoc::error::{Error, Result};
+    use aoc::solution::Solution;
+    use super::*;
+
+    // Part 1 sleeps for as many milliseconds as the input says, so the jobs
+    // finish out of order, then answers with that number.
+    struct Sleepy;
+
+    impl Solution for Sleepy {
+        type Input<'a> = &'a str;
+
+        fn parse<'a>(&self, input: &'a str) -> Result<&'a str> {
+            Ok(input)
+        }
+
+        fn part1(&self, input: &&str) -> Result<Answer> {
+            match *input {
+                "panic" => panic!("asked to"),
+                "error" => Err(Error::no_solution("asked to")),
+                ms => {
+                    let ms : u64 = ms.parse().unwrap();
+                    thread::sleep(Duration::from_millis(ms));
+                    Ok(ms.into())
+                }
+            }
+        }
+
+        fn part2(&self, _input: &&str) -> Result<Answer> {
+            Ok(0.into())
+        }
+
+        fn example_answers(&self) -> [Answer; 2] {
+            [0.into(), 0.into()]
+        }
+    }
+
+    #[test]
+    fn rows_come_back_in_job_order_and_panics_are_caught() {
+        let source = InputSource::Stdin;
+        let inputs = ["40", "panic", "0", "error", "20", "10"];
+        let jobs : Vec<Job> = inputs.iter().enumerate().map(|(i, input)| Job {
+            solution: &Sleepy, year: 2022, day: i as u32 + 1, part: 1, input, source: &source, example: false,
+        }).collect();
+
+        for threads in [1, 3, 8] {
+            let rows = run_jobs(&jobs, threads);
+            let days : Vec<u32> = rows.iter().map(|row| row.day).collect();
+            assert_eq!(days, [1, 2, 3, 4, 5, 6]);
+            let answers : Vec<String> = rows.iter().map(|row| match &row.run {
+                Ok(run) => run.answer.to_string(),
+                Err(failure) => failure.to_string(),
+            }).collect();
+            assert_eq!(answers, ["40", "panicked", "0", "No solution: asked to", "20", "10"], "with {} threads", threads);
+        }
+    }
+}
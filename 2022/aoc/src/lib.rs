@@ -0,0 +1,43 @@
+#[macro_use]
+extern crate lazy_static;
+
+// Lets #[derive(FromPair)] name this crate as ::aoc from the inside too.
+extern crate self as aoc;
+
+pub mod grid;
+mod a_star;
+mod quad;
+mod weighted_value;
+pub mod error;
+pub mod parsing;
+pub mod util;
+pub mod answer;
+pub mod solution;
+
+use solution::solutions;
+
+solutions! {
+    2022 => {
+        1 => day1::Day1,
+        2 => day2::Day2,
+        3 => day3::Day3,
+        4 => day4::Day4,
+        5 => day5::Day5,
+        6 => day6::Day6,
+        7 => day7::Day7,
+        8 => day8::Day8,
+        9 => day9::Day9,
+        10 => day10::Day10,
+        11 => day11::Day11,
+        12 => day12::Day12,
+        13 => day13::Day13,
+        14 => day14::Day14,
+        15 => day15::Day15,
+        16 => day16::Day16,
+        17 => day17::Day17,
+        18 => day18::Day18,
+        19 => day19::Day19,
+        20 => day20::Day20,
+        21 => day21::Day21,
+    }
+}
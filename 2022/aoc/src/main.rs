@@ -1,101 +1,146 @@
-#[macro_use]
-extern crate pest_derive;
+mod bench;
+mod cli;
+mod jobs;
+mod report;
 
-#[macro_use]
-extern crate lazy_static;
+use std::env;
+use std::process::exit;
+use std::time::Instant;
+use aoc::answer::Answer;
+use aoc::registry;
+use aoc::solution::Registry;
+use aoc::util::InputSource;
+use bench::bench;
+use cli::{parse_args, AllArgs, Command, RunArgs, USAGE};
+use jobs::{run_jobs, Job};
+use report::{format_duration, print_csv, print_json, print_table, Failure, Format, Row, Summary};
 
-mod grid;
-mod util;
-mod parsing;
-mod a_star;
-mod quad;
-mod weighted_value;
+fn day_source(day: u32, example: bool) -> InputSource {
+    if example {
+        InputSource::example_for_day(day)
+    } else {
+        InputSource::for_day(day)
+    }
+}
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
+fn read_input(source: &InputSource) -> Result<String, String> {
+    source.read().map_err(|err| err.to_string())
+}
 
-use std::env;
+// The registered days of `year` (or the latest year), limited to `days` if given.
+fn select_days(registry: &Registry, year: Option<u32>, days: Option<&Vec<u32>>) -> Result<(u32, Vec<u32>), String> {
+    let year = year.or(registry.latest_year()).ok_or("No solutions registered")?;
+    let selected : Vec<u32> = registry.days()
+        .filter(|(y, day)| *y == year && days.is_none_or(|days| days.contains(day)))
+        .map(|(_, day)| day)
+        .collect();
+    if selected.is_empty() {
+        return Err(format!("No solutions for {} in the given days", year));
+    }
+    Ok((year, selected))
+}
 
-fn run_part(day: i32, part: i32) {
-    match (day,part) {
-        (1,1) => day1::part1(),
-        (1,2) => day1::part2(),
-        (2,1) => day2::part1(),
-        (2,2) => day2::part2(),
-        (3,1) => day3::part1(),
-        (3,2) => day3::part2(),
-        (4,1) => day4::part1(),
-        (4,2) => day4::part2(),
-        (5,1) => day5::part1(),
-        (5,2) => day5::part2(),
-        (6,1) => day6::part1(),
-        (6,2) => day6::part2(),
-        (7,1) => day7::part1(),
-        (7,2) => day7::part2(),
-        (8,1) => day8::part1(),
-        (8,2) => day8::part2(),
-        (9,1) => day9::part1(),
-        (9,2) => day9::part2(),
-        (10,1) => day10::part1(),
-        (10,2) => day10::part2(),
-        (11,1) => day11::part1(),
-        (11,2) => day11::part2(),
-        (12,1) => day12::part1(),
-        (12,2) => day12::part2(),
-        (13,1) => day13::part1(),
-        (13,2) => day13::part2(),
-        (14,1) => day14::part1(),
-        (14,2) => day14::part2(),
-        (15,1) => day15::part1(),
-        (15,2) => day15::part2(),
-        (16,1) => day16::part1(),
-        (16,2) => day16::part2(),
-        (17,1) => day17::part1(),
-        (17,2) => day17::part2(),
-        (18,1) => day18::part1(),
-        (18,2) => day18::part2(),
-        (19,1) => day19::part1(),
-        (19,2) => day19::part2(),
-        (20,1) => day20::part1(),
-        (20,2) => day20::part2(),
-        (21,1) => day21::part1(),
-        (21,2) => day21::part2(),
-        _ => println!("Invalid args"),
+fn timed_run_jobs(jobs: &[Job], threads: usize) -> (Vec<Row>, Summary) {
+    let start = Instant::now();
+    let rows = run_jobs(jobs, threads);
+    (rows, Summary { wall_time: start.elapsed(), jobs: threads })
+}
+
+fn print_rows(rows: &[Row], summary: &Summary, format: Format) {
+    match format {
+        Format::Table => print_table(rows, summary),
+        Format::Json => print_json(rows),
+        Format::Csv => print_csv(rows),
+    }
+    if format != Format::Table {
+        eprintln!("Ran {} part(s) in {} wall clock with {} job(s)",
+                  rows.len(), format_duration(summary.wall_time), summary.jobs);
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        3 => {
-            match (args[1].parse(), args[2].parse()) {
-                (Ok(day),Ok(1)) => run_part(day, 1),
-                (Ok(day),Ok(2)) => run_part(day, 2),
-                _ => println!("Invalid args"),
-            }
+fn check_rows(rows: &[Row]) -> Result<(), String> {
+    let failed = rows.iter().filter(|row| row.failed()).count();
+    if failed > 0 {
+        return Err(format!("{} part(s) failed", failed));
+    }
+    Ok(())
+}
 
+fn run_part(registry: &Registry, args: RunArgs) -> Result<(), String> {
+    let year = args.year.or(registry.latest_year()).ok_or("No solutions registered")?;
+    let solution = registry.get(year, args.day)
+        .ok_or(format!("No solution for {} day {}", year, args.day))?;
+    let source = args.input.unwrap_or_else(|| day_source(args.day, args.example));
+    let input = read_input(&source)?;
+    let parts = args.part.map(|part| vec![part]).unwrap_or(vec![1, 2]);
+    let jobs : Vec<Job> = parts.into_iter()
+        .map(|part| Job { solution, year, day: args.day, part, input: &input, source: &source, example: args.example })
+        .collect();
+    let (rows, summary) = timed_run_jobs(&jobs, args.jobs);
+    if args.format != Format::Table {
+        print_rows(&rows, &summary, args.format);
+        return check_rows(&rows);
+    }
+
+    for row in &rows {
+        let run = match &row.run {
+            Ok(run) => run,
+            Err(Failure::Panicked) => return Err(format!("Part {} panicked", row.part)),
+            Err(Failure::Error(err)) => return Err(err.to_string()),
+        };
+        let answer = &run.answer;
+        let sep = if matches!(answer, Answer::Ascii(_)) { "\n" } else { " " };
+        if let Some(expected) = &row.expected {
+            if answer == expected {
+                println!("Part {}:{}{} (ok)", row.part, sep, answer);
+            } else {
+                println!("Part {}:{}{} (FAIL, expected{}{})", row.part, sep, answer, sep, expected);
+            }
+        } else if args.part.is_some() {
+            println!("{}", answer);
+        } else {
+            println!("Part {}:{}{}", row.part, sep, answer);
         }
-        _ => {
-          println!("usage: aoc day 1/2")
-        }
+    }
+    check_rows(&rows)
+}
+
+fn run_all(registry: &Registry, args: AllArgs) -> Result<(), String> {
+    let (year, days) = select_days(registry, args.year, args.days.as_ref())?;
+    let sources : Vec<InputSource> = days.iter().map(|&day| day_source(day, args.example)).collect();
+    let inputs = sources.iter()
+        .map(read_input)
+        .collect::<Result<Vec<_>, _>>()?;
+    let jobs : Vec<Job> = days.iter().zip(sources.iter().zip(&inputs))
+        .flat_map(|(&day, (source, input))| [1, 2].map(|part| Job {
+            solution: registry.get(year, day).unwrap(),
+            year, day, part, input, source,
+            example: args.example,
+        }))
+        .collect();
+    let (rows, summary) = timed_run_jobs(&jobs, args.jobs);
+    print_rows(&rows, &summary, args.format);
+    check_rows(&rows)
+}
+
+fn list(registry: &Registry) {
+    for (year, day) in registry.days() {
+        println!("{} day {}", year, day);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let registry = registry();
+    let result = match parse_args(&args, &registry.years()) {
+        Ok(Command::List) => { list(&registry); Ok(()) }
+        Ok(Command::Run(run_args)) => run_part(&registry, run_args),
+        Ok(Command::All(all_args)) => run_all(&registry, all_args),
+        Ok(Command::Bench(bench_args)) => bench(&registry, bench_args),
+        Err(err) => Err(format!("{}\n{}", err, USAGE)),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        exit(1);
     }
 }
@@ -1,41 +1,132 @@
-use std::collections::HashSet;
-use std::fmt::Debug;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
 use std::fs::read_to_string;
 use std::hash::Hash;
+use std::path::Path;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::Arc;
-use pest::iterators::Pair;
-use pest::{Parser, RuleType};
+use pest::iterators::{Pair, Pairs};
+use pest::{Parser, RuleType, Span};
+use crate::error::{Error, ParseError, Result};
+
+pub use aoc_derive::{pest_grammar, FromPair};
+
+pub mod lines;
+pub mod symbol;
+
+pub use symbol::{Symbol, Symbols};
 
 pub trait ParseFile<R: RuleType> {
-    fn parse_file<T: FromPair<R>>(rule: R, filename: &str) -> T;
+    // `T` may borrow from `input`, with &str fields.
+    fn parse_str<'i, T>(rule: R, input: &'i str) -> Result<T, ParseError>
+        where T: TryFromPair<'i, R>, T::Error: Into<ParseError>;
+    // Like parse_str, but reads the input from `path` and names it in errors.
+    fn try_parse_file<T>(rule: R, path: impl AsRef<Path>) -> Result<T>
+        where T: for<'i> TryFromPair<'i, R>, for<'i> <T as TryFromPair<'i, R>>::Error: Into<ParseError>;
 }
 
 impl<R: RuleType, P: Parser<R>> ParseFile<R> for P {
-    fn parse_file<T: FromPair<R>>(rule: R, filename: &str) -> T {
-        let input = read_to_string(filename).unwrap();
-        match P::parse(rule, &input) {
-            Ok(mut pairs) => {
-                pairs.parse_next()
-            }
-            Err(err) => {
-                println!("Error parsing {}: {}", filename, err);
-                panic!();
-            }
+    fn parse_str<'i, T>(rule: R, input: &'i str) -> Result<T, ParseError>
+        where T: TryFromPair<'i, R>, T::Error: Into<ParseError>
+    {
+        let pairs = P::parse(rule, input)?;
+        InnerPairs { pairs, span: Span::new(input, 0, input.len()).unwrap(), rule }.try_parse_next()
+    }
+
+    fn try_parse_file<T>(rule: R, path: impl AsRef<Path>) -> Result<T>
+        where T: for<'i> TryFromPair<'i, R>, for<'i> <T as TryFromPair<'i, R>>::Error: Into<ParseError>
+    {
+        let path = path.as_ref();
+        let input = read_to_string(path)
+            .map_err(|source| Error::Io { path: path.display().to_string(), source })?;
+        Self::parse_str(rule, &input).map_err(|err| Error::from(err).in_file(path.display()))
+    }
+}
+
+// An error covering `pair`, naming its rule.
+pub fn pair_error<R: RuleType>(pair: &Pair<R>, message: impl Into<String>) -> ParseError {
+    span_error(pair.as_span(), pair.as_rule(), message)
+}
+
+fn span_error<R: RuleType>(span: Span, rule: R, message: impl Into<String>) -> ParseError {
+    let (line, column) = span.start_pos().line_col();
+    ParseError {
+        end: Some(span.end_pos().line_col()),
+        rule: Some(format!("{:?}", rule)),
+        ..ParseError::new(line, column, message)
+    }
+}
+
+// 'i is the lifetime of the input text, for types that borrow from it.
+pub trait TryFromPair<'i, R>: Sized {
+    type Error;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error>;
+}
+
+// The pairs inside one, remembering where it was so that running out of
+// them can be reported there.
+pub struct InnerPairs<'i, R> {
+    pairs: Pairs<'i, R>,
+    span: Span<'i>,
+    rule: R,
+}
+
+impl<'i, R: RuleType> InnerPairs<'i, R> {
+    pub fn of(pair: Pair<'i, R>) -> Self {
+        Self { span: pair.as_span(), rule: pair.as_rule(), pairs: pair.into_inner() }
+    }
+
+    pub fn peek(&self) -> Option<Pair<'i, R>> {
+        self.pairs.peek()
+    }
+}
+
+impl<'i, R: RuleType> Iterator for InnerPairs<'i, R> {
+    type Item = Pair<'i, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pairs.next()
+    }
+}
+
+pub trait TryParseNext<'i, R> {
+    fn try_parse_next<T>(&mut self) -> Result<T, ParseError>
+        where T: TryFromPair<'i, R>, T::Error: Into<ParseError>;
+}
+
+impl<'i, R: RuleType> TryParseNext<'i, R> for InnerPairs<'i, R> {
+    fn try_parse_next<T>(&mut self) -> Result<T, ParseError>
+        where T: TryFromPair<'i, R>, T::Error: Into<ParseError>
+    {
+        match self.pairs.next() {
+            Some(pair) => T::try_from_pair(pair).map_err(Into::into),
+            None => Err(span_error(self.span, self.rule, "missing pair")),
         }
     }
 }
 
-pub trait FromPair<R> {
-    fn from_pair(pair: Pair<R>) -> Self;
+// The infallible interface, for when a failure can only be a bug.
+pub trait FromPair<'i, R> {
+    fn from_pair(pair: Pair<'i, R>) -> Self;
+}
+
+impl<'i, R, T> FromPair<'i, R> for T
+    where
+        T: TryFromPair<'i, R>,
+        T::Error: Display
+{
+    fn from_pair(pair: Pair<'i, R>) -> Self {
+        T::try_from_pair(pair).unwrap_or_else(|err| panic!("{}", err))
+    }
 }
 
 pub trait ParseInto<T> {
     fn parse_into(self) -> T;
 }
 
-impl<'a, R, T: FromPair<R>> ParseInto<T> for Pair<'a, R> {
+impl<'a, R, T: FromPair<'a, R>> ParseInto<T> for Pair<'a, R> {
     fn parse_into(self) -> T {
         T::from_pair(self)
     }
@@ -45,7 +136,7 @@ pub trait ParseNext<T> {
     fn parse_next(&mut self) -> T;
 }
 
-impl<'a, T,R,O> ParseNext<O> for T
+impl<T,R,O> ParseNext<O> for T
     where
         T: Iterator<Item=R>,
         R: ParseInto<O>
@@ -55,46 +146,136 @@ impl<'a, T,R,O> ParseNext<O> for T
     }
 }
 
-impl<R: RuleType, T: FromPair<R>> FromPair<R> for Vec<T> {
-    fn from_pair(pair: Pair<R>) -> Self {
-        pair.into_inner().map(|r| r.parse_into() ).collect()
+impl<'i, R: RuleType, T: TryFromPair<'i, R>> TryFromPair<'i, R> for Vec<T> {
+    type Error = T::Error;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        pair.into_inner().map(T::try_from_pair).collect()
     }
 }
 
-impl<R: RuleType, T: FromPair<R> + Eq + Hash> FromPair<R> for HashSet<T> {
-    fn from_pair(pair: Pair<R>) -> Self {
-        pair.into_inner().map(|r| r.parse_into() ).collect()
+impl<'i, R: RuleType, T: TryFromPair<'i, R> + Eq + Hash> TryFromPair<'i, R> for HashSet<T> {
+    type Error = T::Error;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        pair.into_inner().map(T::try_from_pair).collect()
     }
 }
 
 
-impl<R: RuleType, T: FromPair<R>> FromPair<R> for Rc<T> {
-    fn from_pair(pair: Pair<R>) -> Self {
-        Rc::new(pair.parse_into())
+// Each inner pair is a rule with a key child then a value child. `insert`
+// returns whether the key was already there.
+fn try_entries<'i, R, K, V>(pair: Pair<'i, R>, mut insert: impl FnMut(K, V) -> bool) -> Result<(), ParseError>
+    where
+        R: RuleType,
+        K: TryFromPair<'i, R>,
+        V: TryFromPair<'i, R>,
+        K::Error: Into<ParseError>,
+        V::Error: Into<ParseError>
+{
+    for entry in pair.into_inner() {
+        let mut pairs = InnerPairs::of(entry.clone());
+        let key_text = pairs.peek().map_or("", |key| key.as_str());
+        let key = pairs.try_parse_next::<K>()?;
+        let value = pairs.try_parse_next::<V>()?;
+        if insert(key, value) {
+            return Err(pair_error(&entry, format!("duplicate key {:?}", key_text)));
+        }
     }
+    Ok(())
 }
 
-impl<R: RuleType, T: FromPair<R>> FromPair<R> for Arc<T> {
-    fn from_pair(pair: Pair<R>) -> Self {
-        Arc::new(pair.parse_into())
+impl<'i, R, K, V> TryFromPair<'i, R> for HashMap<K, V>
+    where
+        R: RuleType,
+        K: TryFromPair<'i, R> + Eq + Hash,
+        V: TryFromPair<'i, R>,
+        K::Error: Into<ParseError>,
+        V::Error: Into<ParseError>
+{
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        let mut map = HashMap::new();
+        try_entries(pair, |key, value| map.insert(key, value).is_some())?;
+        Ok(map)
     }
 }
 
-macro_rules! type_as_parse_next {
-    ($v:ident, $t:ident) => { $v.parse_next() }
+impl<'i, R, K, V> TryFromPair<'i, R> for BTreeMap<K, V>
+    where
+        R: RuleType,
+        K: TryFromPair<'i, R> + Ord,
+        V: TryFromPair<'i, R>,
+        K::Error: Into<ParseError>,
+        V::Error: Into<ParseError>
+{
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        let mut map = BTreeMap::new();
+        try_entries(pair, |key, value| map.insert(key, value).is_some())?;
+        Ok(map)
+    }
+}
+
+impl<'i, R: RuleType, T: TryFromPair<'i, R>, const N: usize> TryFromPair<'i, R> for [T; N]
+    where T::Error: Into<ParseError>
+{
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        let values = pair.clone().into_inner()
+            .map(|inner| T::try_from_pair(inner).map_err(Into::into))
+            .collect::<Result<Vec<T>, ParseError>>()?;
+        let found = values.len();
+        values.try_into()
+            .map_err(|_| pair_error(&pair, format!("expected {} values, found {}", N, found)))
+    }
+}
+
+// For a rule wrapping an optional one, like `maybe_x = { x? }`.
+impl<'i, R: RuleType, T: TryFromPair<'i, R>> TryFromPair<'i, R> for Option<T> {
+    type Error = T::Error;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        pair.into_inner().next().map(T::try_from_pair).transpose()
+    }
 }
 
+impl<'i, R: RuleType, T: TryFromPair<'i, R>> TryFromPair<'i, R> for Rc<T> {
+    type Error = T::Error;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        T::try_from_pair(pair).map(Rc::new)
+    }
+}
+
+impl<'i, R: RuleType, T: TryFromPair<'i, R>> TryFromPair<'i, R> for Arc<T> {
+    type Error = T::Error;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        T::try_from_pair(pair).map(Arc::new)
+    }
+}
+
+// Elements may have different error types, so a tuple reports a ParseError.
 macro_rules! tuple_from_pair {
   ($( $t:ident ),+) => {
     impl<
+        'i,
         R: RuleType,
-        $($t: FromPair<R>),+
-    > FromPair<R> for ($($t),+,) {
-        fn from_pair(pair: Pair<R>) -> Self {
-            let mut pairs = pair.into_inner();
-            (
-                $( type_as_parse_next!(pairs, $t) ),+,
-            )
+        $($t: TryFromPair<'i, R>),+
+    > TryFromPair<'i, R> for ($($t),+,)
+        where $($t::Error: Into<ParseError>),+
+    {
+        type Error = ParseError;
+
+        fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+            let mut pairs = InnerPairs::of(pair);
+            Ok((
+                $( pairs.try_parse_next::<$t>()? ),+,
+            ))
         }
     }
   }
@@ -111,22 +292,222 @@ tuple_from_pair!(T1, T2, T3, T4, T5, T6, T7, T8);
 
 trait FromPairStr: FromStr {}
 
-impl<R,T> FromPair<R> for T
+impl<'i, R, T> TryFromPair<'i, R> for T
 where
     R: RuleType,
     T: FromStr + FromPairStr,
-    T::Err: Debug,
+    T::Err: Display,
 
 {
-    fn from_pair(pair: Pair<R>) -> Self {
-        pair.as_str().parse().unwrap()
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        pair.as_str().parse().map_err(|err| pair_error(&pair, format!("{:?}: {}", pair.as_str(), err)))
     }
 }
 
+impl FromPairStr for i8 {}
+impl FromPairStr for i16 {}
 impl FromPairStr for i32 {}
-impl FromPairStr for u32 {}
 impl FromPairStr for i64 {}
+impl FromPairStr for i128 {}
+impl FromPairStr for isize {}
+impl FromPairStr for u8 {}
+impl FromPairStr for u16 {}
+impl FromPairStr for u32 {}
 impl FromPairStr for u64 {}
+impl FromPairStr for u128 {}
 impl FromPairStr for usize {}
+impl FromPairStr for f64 {}
+impl FromPairStr for char {}
+impl FromPairStr for bool {}
 impl FromPairStr for String {}
 
+// Borrows the pair's text rather than copying it.
+impl<'i: 'a, 'a, R: RuleType> TryFromPair<'i, R> for &'a str {
+    type Error = ParseError;
+
+    fn try_from_pair(pair: Pair<'i, R>) -> Result<Self, Self::Error> {
+        Ok(pair.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(pest_derive::Parser)]
+    #[grammar_inline = r#"
+num = @{ "-"? ~ ASCII_DIGIT+ }
+word = @{ ASCII_ALPHA+ }
+nums = { (num ~ ("," ~ num)*)? }
+value = { (num | word)? }
+entry = { word ~ "=" ~ num }
+entries = { entry ~ ("," ~ entry)* }
+maybe_num = { num? }
+token = @{ (!"," ~ ANY)+ }
+tokens = { token ~ ("," ~ token)* }
+file = { SOI ~ entry ~ ("," ~ NEWLINE? ~ entry)* ~ NEWLINE? ~ EOI }
+sign = @{ "+" | "-" }
+item = { num | word | sign }
+"#]
+    struct TestParser;
+
+    // A second grammar, for #[pair(rule_type)].
+    mod points {
+        #[derive(pest_derive::Parser)]
+        #[grammar_inline = r#"
+coord = @{ ASCII_DIGIT+ }
+point = { coord ~ "," ~ coord }
+"#]
+        pub struct PointParser;
+    }
+
+    fn parse<'i, T>(rule: Rule, input: &'i str) -> Result<T, ParseError>
+        where T: TryFromPair<'i, Rule>, T::Error: Into<ParseError>
+    {
+        TestParser::parse_str(rule, input)
+    }
+
+    #[derive(Debug, FromPair)]
+    enum Value {
+        Num(i32),
+        Word(String),
+    }
+
+    #[test]
+    fn missing_pairs_are_errors() {
+        assert_eq!(parse::<(i32, i32)>(Rule::nums, "1,2").unwrap(), (1, 2));
+        let err = parse::<(i32, i32)>(Rule::nums, "1").unwrap_err();
+        assert_eq!(err.message, "missing pair");
+        assert_eq!((err.line, err.column, err.rule.as_deref()), (1, 1, Some("nums")));
+
+        assert!(matches!(parse::<Value>(Rule::value, "abc").unwrap(), Value::Word(w) if w == "abc"));
+        assert!(matches!(parse::<Value>(Rule::value, "-4").unwrap(), Value::Num(-4)));
+        assert_eq!(parse::<Value>(Rule::value, "").unwrap_err().message, "missing pair");
+    }
+
+    #[test]
+    fn maps_reject_duplicate_keys() {
+        let map : HashMap<String, i32> = parse(Rule::entries, "a=1,b=2").unwrap();
+        assert_eq!(map, HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]));
+        let map : BTreeMap<&str, i32> = parse(Rule::entries, "b=2,a=-1").unwrap();
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), [("a", -1), ("b", 2)]);
+
+        let err = parse::<HashMap<String, i32>>(Rule::entries, "a=1,b=2,a=3").unwrap_err();
+        assert_eq!(err.message, "duplicate key \"a\"");
+        assert_eq!((err.line, err.column, err.end), (1, 9, Some((1, 12))));
+        let err = parse::<BTreeMap<String, i32>>(Rule::entries, "a=1,a=1").unwrap_err();
+        assert_eq!(err.message, "duplicate key \"a\"");
+    }
+
+    #[test]
+    fn arrays_need_the_right_length() {
+        assert_eq!(parse::<[i32; 3]>(Rule::nums, "1,2,3").unwrap(), [1, 2, 3]);
+        let err = parse::<[i32; 3]>(Rule::nums, "1,2").unwrap_err();
+        assert_eq!(err.message, "expected 3 values, found 2");
+        assert_eq!(err.rule.as_deref(), Some("nums"));
+        assert_eq!(parse::<[i32; 1]>(Rule::nums, "1,2").unwrap_err().message, "expected 1 values, found 2");
+        // An element's own error comes first.
+        assert!(parse::<[u8; 2]>(Rule::nums, "1,300").unwrap_err().message.starts_with("\"300\""));
+    }
+
+    #[test]
+    fn options_read_optional_rules() {
+        assert_eq!(parse::<Option<i32>>(Rule::maybe_num, "7").unwrap(), Some(7));
+        assert_eq!(parse::<Option<i32>>(Rule::maybe_num, "").unwrap(), None);
+        assert!(parse::<Option<u8>>(Rule::maybe_num, "-7").is_err());
+    }
+
+    #[test]
+    fn scalars_report_bad_text() {
+        assert_eq!(parse::<Vec<char>>(Rule::tokens, "a,b").unwrap(), ['a', 'b']);
+        let err = parse::<Vec<char>>(Rule::tokens, "a,bc").unwrap_err();
+        assert_eq!((err.column, err.rule.as_deref()), (3, Some("token")));
+        assert!(err.message.starts_with("\"bc\": "));
+
+        assert_eq!(parse::<Vec<bool>>(Rule::tokens, "true,false").unwrap(), [true, false]);
+        assert!(parse::<Vec<bool>>(Rule::tokens, "true,yes").unwrap_err().message.starts_with("\"yes\": "));
+
+        let big = "170141183460469231731687303715884105727";
+        assert_eq!(parse::<Vec<i128>>(Rule::tokens, big).unwrap(), [i128::MAX]);
+        let err = parse::<Vec<i128>>(Rule::tokens, "170141183460469231731687303715884105728").unwrap_err();
+        assert!(err.message.ends_with("number too large to fit in target type"), "{}", err.message);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let err = parse::<(HashMap<String, i32>,)>(Rule::file, "a=1,\nb=x\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.end), (2, 3, None));
+        assert_eq!(err.rule.as_deref(), Some("num"));
+        assert_eq!(err.to_string(), "line 2, column 3: expected num");
+
+        // Conversion errors cover the pair they're about.
+        let input = "a=1,\nbb=2,\nbb=300\n";
+        let err = parse::<HashMap<String, i32>>(Rule::file, input).unwrap_err();
+        assert_eq!((err.line, err.column, err.end), (3, 1, Some((3, 7))));
+        assert_eq!(err.rule.as_deref(), Some("entry"));
+        assert_eq!(err.to_string(), "line 3, column 1: duplicate key \"bb\" (in entry)");
+        let err = parse::<HashMap<String, u8>>(Rule::file, input).unwrap_err();
+        assert_eq!((err.line, err.column, err.end), (3, 4, Some((3, 7))));
+        assert_eq!(err.rule.as_deref(), Some("num"));
+
+        let err = Error::from(err).in_file("input.txt");
+        assert_eq!(err.to_string(), "Parse error at input.txt:3:4: \"300\": number too large to fit in target type (in num)");
+    }
+
+    #[derive(Debug, PartialEq, FromPair)]
+    struct Setting {
+        name: String,
+        #[pair(skip)]
+        seen: bool,
+        #[pair(default = "10")]
+        scale: i32,
+        value: i32,
+    }
+
+    #[test]
+    fn skipped_fields_are_default() {
+        let setting : Setting = parse(Rule::entry, "a=3").unwrap();
+        assert!(!setting.seen);
+        assert_eq!((setting.name.as_str(), setting.value), ("a", 3));
+    }
+
+    #[test]
+    fn defaulted_fields_take_their_expression() {
+        let setting : Setting = parse(Rule::entry, "a=3").unwrap();
+        assert_eq!(setting, Setting { name: "a".into(), seen: false, scale: 10, value: 3 });
+        // It doesn't take a pair, so the next field still reads the number.
+        assert_eq!(parse::<Setting>(Rule::entry, "a=x").unwrap_err().rule.as_deref(), Some("num"));
+    }
+
+    #[derive(Debug, PartialEq, FromPair)]
+    #[pair(rule_type = "points::Rule")]
+    struct Point {
+        x: u32,
+        y: u32,
+    }
+
+    #[test]
+    fn derived_types_can_use_another_grammar() {
+        let point : Point = points::PointParser::parse_str(points::Rule::point, "3,4").unwrap();
+        assert_eq!(point, Point { x: 3, y: 4 });
+    }
+
+    #[derive(Debug, PartialEq, FromPair)]
+    enum Item {
+        Num(i32),
+        #[pair(rule = "word")]
+        Name(String),
+    }
+
+    #[test]
+    fn derived_enums_pick_the_variant_by_rule() {
+        assert_eq!(parse::<Item>(Rule::item, "-2").unwrap(), Item::Num(-2));
+        assert_eq!(parse::<Item>(Rule::item, "ab").unwrap(), Item::Name("ab".into()));
+
+        let err = parse::<Item>(Rule::item, "+").unwrap_err();
+        assert_eq!(err.message, "expected one of num, word");
+        assert_eq!((err.line, err.column, err.end, err.rule.as_deref()), (1, 1, Some((1, 2)), Some("sign")));
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::error::ParseError;
+
+// One line of the input, for the days that split their input by hand
+// instead of writing a grammar. Errors from here carry the line number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line<'a> {
+    // 1-based.
+    pub number: usize,
+    pub text: &'a str,
+}
+
+pub fn lines(input: &str) -> impl Iterator<Item=Line<'_>> {
+    input.lines().enumerate().map(|(i, text)| Line { number: i + 1, text })
+}
+
+// The runs of lines between blank lines.
+pub fn line_groups(input: &str) -> Vec<Vec<Line<'_>>> {
+    let all : Vec<Line> = lines(input).collect();
+    all.split(|line| line.text.trim().is_empty())
+        .filter(|group| !group.is_empty())
+        .map(|group| group.to_vec())
+        .collect()
+}
+
+// Parses `token`, found at `column` of `line` (both 1-based).
+pub fn parse_token<T: FromStr>(token: &str, line: usize, column: usize) -> Result<T, ParseError> {
+    token.parse().map_err(|_| ParseError::new(line, column, format!("unexpected {:?}", token)))
+}
+
+// 1-based column of `token` within `line`, which it must be a slice of.
+pub fn column_of(line: &str, token: &str) -> usize {
+    token.as_ptr() as usize - line.as_ptr() as usize + 1
+}
+
+impl<'a> Line<'a> {
+    pub fn error(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.number, column, message)
+    }
+
+    // An error pointing at `token`, a slice of this line.
+    pub fn error_at(&self, token: &str, message: impl Into<String>) -> ParseError {
+        self.error(column_of(self.text, token), message)
+    }
+
+    pub fn parse<T: FromStr>(&self) -> Result<T, ParseError> {
+        self.parse_token(self.text)
+    }
+
+    pub fn parse_token<T: FromStr>(&self, token: &str) -> Result<T, ParseError> {
+        parse_token(token, self.number, column_of(self.text, token))
+    }
+
+    // Every integer in the line. A '-' is a sign unless it follows a digit,
+    // so "x=-3" gives -3 but "2-4" gives 2 and 4.
+    pub fn ints<T: FromStr>(&self) -> Result<Vec<T>, ParseError> {
+        let bytes = self.text.as_bytes();
+        let mut values = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let negative = bytes[i] == b'-'
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && (i == 0 || !bytes[i - 1].is_ascii_digit());
+            if !negative && !bytes[i].is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            values.push(self.parse_token(&self.text[start..i])?);
+        }
+        Ok(values)
+    }
+
+    // Splits the line by a pattern like "{}-{},{}-{}": the text between the
+    // placeholders must match exactly, and each placeholder takes everything
+    // up to the next piece of text.
+    pub fn fields<const N: usize>(&self, pattern: &str) -> Result<[&'a str; N], ParseError> {
+        let mut pieces = pattern.split("{}");
+        let mut rest = self.text;
+        let mut fields = Vec::with_capacity(N);
+        let mismatch = |at: &str| self.error_at(at, format!("expected a line like {:?}", pattern));
+
+        let prefix = pieces.next().unwrap_or("");
+        rest = rest.strip_prefix(prefix).ok_or_else(|| mismatch(rest))?;
+        let pieces : Vec<&str> = pieces.collect();
+        for (i, literal) in pieces.iter().enumerate() {
+            let end = if literal.is_empty() && i == pieces.len() - 1 {
+                rest.len()
+            } else if literal.is_empty() {
+                return Err(self.error(1, format!("ambiguous pattern {:?}", pattern)));
+            } else {
+                rest.find(literal).ok_or_else(|| mismatch(rest))?
+            };
+            fields.push(&rest[..end]);
+            rest = &rest[end + literal.len()..];
+        }
+        if !rest.is_empty() {
+            return Err(mismatch(rest));
+        }
+        fields.try_into().map_err(|_| self.error(1, format!("pattern {:?} doesn't have {} fields", pattern, N)))
+    }
+
+    // Like fields, but parses each one.
+    pub fn scan<T: FromStr, const N: usize>(&self, pattern: &str) -> Result<[T; N], ParseError> {
+        let fields : [&str; N] = self.fields(pattern)?;
+        let values = fields.iter()
+            .map(|field| self.parse_token(field))
+            .collect::<Result<Vec<T>, _>>()?;
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+// Maps every character of a rectangular grid through `translate`, failing
+// on characters it returns None for and on ragged rows.
+pub fn char_grid<T>(input: &str, mut translate: impl FnMut(char) -> Option<T>) -> Result<Vec<Vec<T>>, ParseError> {
+    try_char_grid(input, |c| translate(c).ok_or_else(|| format!("unexpected {:?}", c)))
+}
+
+// Like char_grid, with `translate` saying what's wrong with a character.
+pub fn try_char_grid<T, E: Display>(input: &str, mut translate: impl FnMut(char) -> Result<T, E>) -> Result<Vec<Vec<T>>, ParseError> {
+    let mut rows : Vec<Vec<T>> = Vec::new();
+    for line in lines(input) {
+        let row = line.text.chars().enumerate()
+            .map(|(i, c)| translate(c).map_err(|err| line.error(i + 1, err.to_string())))
+            .collect::<Result<Vec<T>, _>>()?;
+        if let Some(first) = rows.first() {
+            if row.len() != first.len() {
+                return Err(line.error(1, format!("expected {} columns, found {}", first.len(), row.len())));
+            }
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// char_grid with a fixed table of characters and what they stand for.
+pub fn char_grid_table<T: Clone>(input: &str, table: &[(char, T)]) -> Result<Vec<Vec<T>>, ParseError> {
+    char_grid(input, |c| table.iter().find(|(from, _)| *from == c).map(|(_, to)| to.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> Line<'_> {
+        Line { number: 3, text }
+    }
+
+    #[test]
+    fn ints_take_signs_only_before_digits() {
+        assert_eq!(line("x=-3, y=4").ints::<i32>().unwrap(), [-3, 4]);
+        assert_eq!(line("2-4,-6--8").ints::<i32>().unwrap(), [2, 4, -6, -8]);
+        assert_eq!(line("a - b -").ints::<i32>().unwrap(), Vec::<i32>::new());
+        let err = line("move -3").ints::<u32>().unwrap_err();
+        assert_eq!((err.line, err.column, err.message.as_str()), (3, 6, "unexpected \"-3\""));
+    }
+
+    #[test]
+    fn fields_follow_the_pattern() {
+        assert_eq!(line("2-4,6-8").fields::<4>("{}-{},{}-{}").unwrap(), ["2", "4", "6", "8"]);
+        assert_eq!(line("move 3 from 1 to 2").scan::<u32, 3>("move {} from {} to {}").unwrap(), [3, 1, 2]);
+
+        let err = line("2-4,6-8").fields::<3>("{}-{},{}-{}").unwrap_err();
+        assert_eq!(err.message, "pattern \"{}-{},{}-{}\" doesn't have 3 fields");
+        let err = line("2-4;6-8").fields::<4>("{}-{},{}-{}").unwrap_err();
+        assert_eq!((err.column, err.message.as_str()), (3, "expected a line like \"{}-{},{}-{}\""));
+        let err = line("move x from 1 to 2").scan::<u32, 3>("move {} from {} to {}").unwrap_err();
+        assert_eq!((err.column, err.message.as_str()), (6, "unexpected \"x\""));
+    }
+
+    #[test]
+    fn char_grids_must_be_rectangular() {
+        let digit = |c: char| c.to_digit(10);
+        assert_eq!(char_grid("12\n34\n", digit).unwrap(), [[1, 2], [3, 4]]);
+        let err = char_grid("12\n345\n", digit).unwrap_err();
+        assert_eq!((err.line, err.column, err.message.as_str()), (2, 1, "expected 2 columns, found 3"));
+        let err = char_grid("12\n3x\n", digit).unwrap_err();
+        assert_eq!((err.line, err.column, err.message.as_str()), (2, 2, "unexpected 'x'"));
+        assert_eq!(char_grid_table("#.\n", &[('#', true), ('.', false)]).unwrap(), [[true, false]]);
+    }
+}
@@ -0,0 +1,46 @@
+// A name from one input, as a number: Copy, and compared and hashed as
+// cheaply as one. Symbols come from the input's Symbols, which are
+// numbered in name order, so they order as their names do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    // From 0 to one less than the number of names, for indexing by.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// The names in one input, borrowed from it. Nothing is shared between
+// inputs, so there's no locking and nothing outlives the input. Looking a
+// name up is a binary search, which beats hashing for the few hundred
+// names a puzzle has.
+#[derive(Clone, Debug, Default)]
+pub struct Symbols<'a> {
+    names: Vec<&'a str>,
+}
+
+impl<'a> Symbols<'a> {
+    pub fn new(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut names : Vec<&'a str> = names.into_iter().collect();
+        names.sort_unstable();
+        names.dedup();
+        Self { names }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.names.binary_search(&name).ok().map(|i| Symbol(i as u32))
+    }
+
+    pub fn name(&self, symbol: Symbol) -> &'a str {
+        self.names[symbol.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -19,7 +19,7 @@ impl<T: Copy + Default> Quad<T> {
         for (cost, index) in costs {
             cust_arr[(*index).into()] = *cost;
         }
-        return Self(cust_arr);
+        Self(cust_arr)
     }
 }
 impl<T: Copy + Add<Output=T>> Quad<T> {
@@ -0,0 +1,264 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+use aoc::answer::Answer;
+use aoc::error::Error;
+use aoc::solution::PartRun;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format { Table, Json, Csv }
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format: {}", s)),
+        }
+    }
+}
+
+// Totals for a whole invocation, as opposed to the per-part times.
+pub struct Summary {
+    pub wall_time: Duration,
+    pub jobs: usize,
+}
+
+// Why a part has no answer.
+pub enum Failure {
+    Panicked,
+    Error(Error),
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Panicked => write!(f, "panicked"),
+            Failure::Error(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+pub struct Row {
+    pub year: u32,
+    pub day: u32,
+    pub part: u32,
+    pub input_hash: u64,
+    pub run: Result<PartRun, Failure>,
+    // Only set when checking the examples.
+    pub expected: Option<Answer>,
+}
+
+impl Row {
+    pub fn status(&self) -> &'static str {
+        match (&self.run, &self.expected) {
+            (Err(Failure::Panicked), _) => "panicked",
+            (Err(Failure::Error(_)), _) => "error",
+            (Ok(run), Some(expected)) if run.answer != *expected => "fail",
+            _ => "ok",
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        self.status() != "ok"
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.run.as_ref().err().map(|failure| failure.to_string())
+    }
+
+    fn answer_cell(&self) -> String {
+        let Ok(run) = &self.run else {
+            return self.status().into();
+        };
+        let answer = match &run.answer {
+            Answer::Ascii(_) => "(see below)".to_string(),
+            answer => answer.to_string(),
+        };
+        match &self.expected {
+            Some(expected) if *expected != run.answer => format!("{} (expected {})", answer, expected),
+            _ => answer,
+        }
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1e6;
+    if micros < 1e3 {
+        format!("{:.1}µs", micros)
+    } else if micros < 1e6 {
+        format!("{:.1}ms", micros / 1e3)
+    } else {
+        format!("{:.2}s", micros / 1e6)
+    }
+}
+
+pub fn print_table(rows: &[Row], summary: &Summary) {
+    let cells : Vec<String> = rows.iter().map(|row| row.answer_cell()).collect();
+    let width = cells.iter().map(|c| c.chars().count()).max().unwrap_or(0).max("Answer".len());
+    println!("{:>3}  {:>4}  {:<width$}  {:>9}  {:>9}  {:>9}", "Day", "Part", "Answer", "Parse", "Solve", "CPU");
+
+    let (mut total_parse, mut total_solve, mut total_cpu) = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    for (row, cell) in rows.iter().zip(&cells) {
+        let [parse, solve, cpu] = match &row.run {
+            Ok(run) => {
+                total_parse += run.parse_time;
+                total_solve += run.solve_time;
+                total_cpu += run.cpu_time.unwrap_or_default();
+                [
+                    format_duration(run.parse_time),
+                    format_duration(run.solve_time),
+                    run.cpu_time.map(format_duration).unwrap_or("-".into()),
+                ]
+            }
+            Err(_) => ["-".into(), "-".into(), "-".into()],
+        };
+        println!("{:>3}  {:>4}  {:<width$}  {:>9}  {:>9}  {:>9}", row.day, row.part, cell, parse, solve, cpu);
+    }
+    println!("{:<9}  {:<width$}  {:>9}  {:>9}  {:>9}", "Total", "",
+             format_duration(total_parse), format_duration(total_solve), format_duration(total_cpu));
+    println!("Wall clock: {} with {} job(s)", format_duration(summary.wall_time), summary.jobs);
+
+    for row in rows {
+        match &row.run {
+            Ok(PartRun { answer: answer @ Answer::Ascii(_), .. }) => {
+                println!("\nDay {} part {}:\n{}", row.day, row.part, answer);
+            }
+            Err(Failure::Error(err)) => println!("\nDay {} part {}: {}", row.day, row.part, err),
+            _ => {}
+        }
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1e3)
+}
+
+// Parse, solve, total and CPU time in milliseconds, with `missing` for
+// times that weren't measured.
+fn timings(row: &Row, missing: &str) -> [String; 4] {
+    match &row.run {
+        Ok(run) => [
+            millis(run.parse_time),
+            millis(run.solve_time),
+            millis(run.parse_time + run.solve_time),
+            run.cpu_time.map(millis).unwrap_or(missing.into()),
+        ],
+        Err(_) => [missing.into(), missing.into(), missing.into(), missing.into()],
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_answer(answer: Option<&Answer>) -> String {
+    match answer {
+        Some(Answer::Int(n)) => n.to_string(),
+        Some(answer) => json_string(&answer.to_string()),
+        None => "null".into(),
+    }
+}
+
+// One JSON object per row, each on its own line.
+fn json_lines(rows: &[Row]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let [parse, solve, total, cpu] = timings(row, "null");
+        out.push_str(&format!(
+            "{{\"year\":{},\"day\":{},\"part\":{},\"answer\":{},\"expected\":{},\"parse_ms\":{},\"solve_ms\":{},\"duration_ms\":{},\"cpu_ms\":{},\"input_hash\":\"{:016x}\",\"status\":\"{}\",\"error\":{}}}\n",
+            row.year, row.day, row.part,
+            json_answer(row.run.as_ref().ok().map(|run| &run.answer)),
+            json_answer(row.expected.as_ref()),
+            parse, solve, total, cpu,
+            row.input_hash,
+            row.status(),
+            row.error().map(|err| json_string(&err)).unwrap_or("null".into()),
+        ));
+    }
+    out
+}
+
+pub fn print_json(rows: &[Row]) {
+    print!("{}", json_lines(rows));
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv_lines(rows: &[Row]) -> String {
+    let mut out = String::from("year,day,part,answer,expected,parse_ms,solve_ms,duration_ms,cpu_ms,input_hash,status,error\n");
+    for row in rows {
+        let answer = row.run.as_ref().map(|run| run.answer.to_string()).unwrap_or_default();
+        let error = row.error().unwrap_or_default();
+        let expected = row.expected.as_ref().map(|e| e.to_string()).unwrap_or_default();
+        let [parse, solve, total, cpu] = timings(row, "");
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{:016x},{},{}\n",
+            row.year, row.day, row.part,
+            csv_field(&answer), csv_field(&expected),
+            parse, solve, total, cpu,
+            row.input_hash,
+            row.status(),
+            csv_field(&error),
+        ));
+    }
+    out
+}
+
+pub fn print_csv(rows: &[Row]) {
+    print!("{}", csv_lines(rows));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Row> {
+        let ms = Duration::from_millis;
+        let crt = Answer::Ascii(vec!["#.\"\\".into(), "a,b".into()]);
+        let run = PartRun { answer: crt, parse_time: ms(1), solve_time: ms(2), cpu_time: Some(ms(3)) };
+        let err = Error::no_solution("\"x\", \ty\nz");
+        vec![
+            Row { year: 2022, day: 10, part: 2, input_hash: 0xabc, run: Ok(run), expected: None },
+            Row { year: 2022, day: 11, part: 1, input_hash: 0, run: Err(Failure::Error(err)), expected: Some(5.into()) },
+        ]
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        assert_eq!(json_lines(&rows()), concat!(
+            r##"{"year":2022,"day":10,"part":2,"answer":"#.\"\\\na,b","expected":null,"parse_ms":1.000,"solve_ms":2.000,"duration_ms":3.000,"cpu_ms":3.000,"input_hash":"0000000000000abc","status":"ok","error":null}"##, "\n",
+            r##"{"year":2022,"day":11,"part":1,"answer":null,"expected":5,"parse_ms":null,"solve_ms":null,"duration_ms":null,"cpu_ms":null,"input_hash":"0000000000000000","status":"error","error":"No solution: \"x\", \u0009y\nz"}"##, "\n",
+        ));
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        assert_eq!(csv_lines(&rows()), concat!(
+            "year,day,part,answer,expected,parse_ms,solve_ms,duration_ms,cpu_ms,input_hash,status,error\n",
+            "2022,10,2,\"#.\"\"\\\na,b\",,1.000,2.000,3.000,3.000,0000000000000abc,ok,\n",
+            "2022,11,1,,5,,,,,0000000000000000,error,\"No solution: \"\"x\"\", \ty\nz\"\n",
+        ));
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
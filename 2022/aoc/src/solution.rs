@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use crate::util::thread_cpu_time;
+
+pub trait Solution: Sync {
+    // May borrow from the input text, to avoid copying names out of it.
+    type Input<'a>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>>;
+    // For days whose example asks a different question of its input than
+    // the real puzzle does.
+    fn parse_example<'a>(&self, input: &'a str) -> Result<Self::Input<'a>> {
+        self.parse(input)
+    }
+    // Checks what the parser can't, like names referring to each other,
+    // before either part runs.
+    fn validate(&self, _input: &Self::Input<'_>, _validator: &mut InputValidator) {}
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Answer>;
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Answer>;
+    // Expected answers for the day's test.txt.
+    fn example_answers(&self) -> [Answer; 2];
+}
+
+// Collects the problems Solution::validate finds in an input.
+#[derive(Debug, Default)]
+pub struct InputValidator {
+    problems: Vec<String>,
+}
+
+impl InputValidator {
+    pub fn problem(&mut self, message: impl Into<String>) {
+        self.problems.push(message.into());
+    }
+
+    pub fn check(&mut self, ok: bool, message: impl FnOnce() -> String) {
+        if !ok {
+            self.problem(message());
+        }
+    }
+
+    // `what` should appear exactly once; `found` is where it did.
+    pub fn exactly_one<T: Display>(&mut self, what: &str, found: &[T]) {
+        match found {
+            [_] => {}
+            [] => self.problem(format!("there is no {}", what)),
+            _ => {
+                let places : Vec<String> = found.iter().map(T::to_string).collect();
+                self.problem(format!("there should be one {}, found {} at {}", what, found.len(), places.join(", ")))
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self.problems.is_empty() {
+            true => Ok(()),
+            false => Err(Error::InvalidInput { file: None, problems: self.problems }),
+        }
+    }
+}
+
+fn parse_valid<'a, S: Solution>(solution: &S, input: &'a str, example: bool) -> Result<S::Input<'a>> {
+    let parsed = if example { solution.parse_example(input)? } else { solution.parse(input)? };
+    let mut validator = InputValidator::default();
+    solution.validate(&parsed, &mut validator);
+    validator.finish()?;
+    Ok(parsed)
+}
+
+#[derive(Clone, Debug)]
+pub struct PartRun {
+    pub answer: Answer,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    // CPU time of this thread over parse and solve, where the platform has it.
+    pub cpu_time: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BenchTimes {
+    pub parse: Vec<Duration>,
+    pub solve: Vec<Duration>,
+}
+
+// Object-safe view of a Solution, so the registry can hold days with
+// different Input types.
+pub trait Runner: Sync {
+    // `example` says the input is the day's example rather than a real one.
+    fn run_part(&self, part: u32, input: &str, example: bool) -> Result<PartRun>;
+    fn example_answer(&self, part: u32) -> Option<Answer>;
+    // Times parsing and solving separately, each up to `runs` times but
+    // stopping early once a phase has used up `budget`.
+    fn bench_part(&self, part: u32, input: &str, example: bool, runs: usize, budget: Duration) -> Result<BenchTimes>;
+}
+
+// Calls `f` until it has run `runs` times or `budget` is used up, always at
+// least once, and returns the time of each call along with the last result.
+fn time_runs<T>(runs: usize, budget: Duration, mut f: impl FnMut() -> Result<T>) -> Result<(Vec<Duration>, T)> {
+    let start = Instant::now();
+    let mut times = Vec::new();
+    loop {
+        let run_start = Instant::now();
+        let result = black_box(f()?);
+        times.push(run_start.elapsed());
+        if times.len() >= runs || start.elapsed() >= budget {
+            return Ok((times, result));
+        }
+    }
+}
+
+type PartFn<S> = fn(&S, &<S as Solution>::Input<'_>) -> Result<Answer>;
+
+fn part_fn<S: Solution>(part: u32) -> Result<PartFn<S>> {
+    match part {
+        1 => Ok(S::part1),
+        2 => Ok(S::part2),
+        _ => Err(Error::InvalidPart(part)),
+    }
+}
+
+impl<S: Solution> Runner for S {
+    fn run_part(&self, part: u32, input: &str, example: bool) -> Result<PartRun> {
+        let solve = part_fn::<S>(part)?;
+        let cpu_start = thread_cpu_time();
+        let start = Instant::now();
+        let parsed = parse_valid(self, input, example)?;
+        let parse_time = start.elapsed();
+        let start = Instant::now();
+        let answer = solve(self, &parsed)?;
+        let solve_time = start.elapsed();
+        let cpu_time = cpu_start.zip(thread_cpu_time()).map(|(start, end)| end - start);
+        Ok(PartRun { answer, parse_time, solve_time, cpu_time })
+    }
+
+    fn example_answer(&self, part: u32) -> Option<Answer> {
+        let [part1, part2] = self.example_answers();
+        match part {
+            1 => Some(part1),
+            2 => Some(part2),
+            _ => None,
+        }
+    }
+
+    fn bench_part(&self, part: u32, input: &str, example: bool, runs: usize, budget: Duration) -> Result<BenchTimes> {
+        let solve = part_fn::<S>(part)?;
+        let (parse, parsed) = time_runs(runs, budget, || parse_valid(self, black_box(input), example))?;
+        let (solve, _) = time_runs(runs, budget, || solve(self, black_box(&parsed)))?;
+        Ok(BenchTimes { parse, solve })
+    }
+}
+
+pub type DayKey = (u32, u32);
+
+#[derive(Default)]
+pub struct Registry {
+    days: BTreeMap<DayKey, &'static dyn Runner>,
+}
+
+impl Registry {
+    pub fn register(&mut self, year: u32, day: u32, solution: &'static dyn Runner) {
+        if self.days.insert((year, day), solution).is_some() {
+            panic!("Day {} of {} registered twice", day, year);
+        }
+    }
+
+    pub fn get(&self, year: u32, day: u32) -> Option<&'static dyn Runner> {
+        self.days.get(&(year, day)).copied()
+    }
+
+    pub fn days(&self) -> impl Iterator<Item=DayKey> + '_ {
+        self.days.keys().copied()
+    }
+
+    pub fn years(&self) -> Vec<u32> {
+        // days() is in order, so a year's days are next to each other.
+        let mut years : Vec<u32> = self.days().map(|(year, _)| year).collect();
+        years.dedup();
+        years
+    }
+
+    pub fn latest_year(&self) -> Option<u32> {
+        self.days.keys().map(|(year, _)| *year).max()
+    }
+}
+
+// Declares each day module and builds the registry from it, so a new day
+// only needs its file and one line here.
+macro_rules! solutions {
+    ($( $year:literal => { $( $day:literal => $module:ident :: $solution:ident ),* $(,)? } )*) => {
+        $( $( mod $module; )* )*
+
+        pub fn registry() -> $crate::solution::Registry {
+            let mut registry = $crate::solution::Registry::default();
+            $( $( registry.register($year, $day, &$module::$solution); )* )*
+            registry
+        }
+    }
+}
+pub(crate) use solutions;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_clean_validator_finishes_ok() {
+        let mut validator = InputValidator::default();
+        validator.check(true, || unreachable!());
+        validator.exactly_one("start", &["line 1"]);
+        assert!(validator.finish().is_ok());
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let mut validator = InputValidator::default();
+        validator.check(false, || "first".into());
+        validator.exactly_one("start", &[] as &[&str]);
+        validator.exactly_one("end", &["line 1", "line 3"]);
+        validator.problem("last");
+        let err = validator.finish().unwrap_err().in_file("input.txt");
+        assert_eq!(err.to_string(), "Invalid input in input.txt: first; there is no start; \
+                                     there should be one end, found 2 at line 1, line 3; last");
+        assert!(matches!(err, Error::InvalidInput { problems, .. } if problems.len() == 4));
+    }
+}
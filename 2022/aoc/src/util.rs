@@ -1,9 +1,66 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-pub fn read_lines<P>(filename: P) -> Vec<String>
-    where P: AsRef<Path>, {
-    let file = File::open(filename).unwrap();
-    io::BufReader::new(file).lines().map(|l| l.unwrap()).collect()
-}
\ No newline at end of file
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::error::{Error, Result};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    pub fn for_day(day: u32) -> Self {
+        Self::File(day_dir(day).join("input.txt"))
+    }
+
+    pub fn example_for_day(day: u32) -> Self {
+        Self::File(day_dir(day).join("test.txt"))
+    }
+
+    pub fn read(&self) -> Result<String> {
+        let result = match self {
+            Self::File(path) => read_to_string(path),
+            Self::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input).map(|_| input)
+            }
+        };
+        result.map_err(|source| Error::Io { path: self.to_string(), source })
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Stdin => f.write_str("stdin"),
+        }
+    }
+}
+
+// FNV-1a, so the hash stays the same across builds and Rust versions.
+pub fn input_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// CPU time used so far by the calling thread.
+#[cfg(unix)]
+pub fn thread_cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    let ret = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+    (ret == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+pub fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+pub fn day_dir(day: u32) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs").join(format!("day{}", day))
+}
@@ -22,7 +22,7 @@ impl<W: PartialEq + Eq + PartialOrd + Ord + Clone, T: Clone> Eq for WeightedValu
 
 impl<W: PartialEq + Eq + PartialOrd + Ord + Clone, T: Clone> PartialOrd for WeightedValue<W,T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.weight.partial_cmp(&other.weight)
+        Some(self.cmp(other))
     }
 }
 
@@ -0,0 +1,116 @@
+use std::fs::read_to_string;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use aoc::answer::Answer;
+use aoc::registry;
+use aoc::solution::Runner;
+use aoc::util::InputSource;
+use toml::{Table, Value};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind { Input, Example }
+
+struct Case {
+    year: u32,
+    day: u32,
+    part: u32,
+    kind: Kind,
+    expected: Option<Answer>,
+    slow: bool,
+}
+
+fn answers_file(year: u32) -> Table {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("answers").join(format!("{}.toml", year));
+    read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Error reading {}: {}", path.display(), err))
+        .parse()
+        .unwrap_or_else(|err| panic!("Error parsing {}: {}", path.display(), err))
+}
+
+fn to_answer(value: &Value) -> Answer {
+    match value {
+        Value::Integer(n) => Answer::Int(*n),
+        Value::String(s) if s.contains('\n') => Answer::Ascii(s.lines().map(String::from).collect()),
+        Value::String(s) => Answer::Str(s.clone()),
+        v => panic!("Unsupported answer: {}", v),
+    }
+}
+
+fn is_slow(day: Option<&Table>, key: &str, part: u32) -> bool {
+    day.and_then(|d| d.get(key))
+        .and_then(|v| v.as_array())
+        .is_some_and(|parts| parts.iter().any(|p| p.as_integer() == Some(part as i64)))
+}
+
+fn cases() -> Vec<Case> {
+    let registry = registry();
+    let mut cases = Vec::new();
+    let mut years: Vec<u32> = registry.days().map(|(year, _)| year).collect();
+    years.dedup();
+    for year in years {
+        let answers = answers_file(year);
+        for (_, day) in registry.days().filter(|(y, _)| *y == year) {
+            let solution = registry.get(year, day).unwrap();
+            let table = answers.get(&format!("day{}", day)).and_then(|v| v.as_table());
+            for part in [1, 2] {
+                cases.push(Case {
+                    year, day, part,
+                    kind: Kind::Input,
+                    expected: table.and_then(|t| t.get(&format!("part{}", part))).map(to_answer),
+                    slow: is_slow(table, "slow", part),
+                });
+                cases.push(Case {
+                    year, day, part,
+                    kind: Kind::Example,
+                    expected: solution.example_answer(part),
+                    slow: is_slow(table, "slow_example", part),
+                });
+            }
+        }
+    }
+    cases
+}
+
+fn run_case(solution: &dyn Runner, case: &Case) -> Result<(), String> {
+    let expected = case.expected.as_ref().ok_or("no expected answer")?;
+    let source = match case.kind {
+        Kind::Input => InputSource::for_day(case.day),
+        Kind::Example => InputSource::example_for_day(case.day),
+    };
+    let input = source.read().map_err(|err| err.to_string())?;
+    let answer = catch_unwind(AssertUnwindSafe(|| solution.run_part(case.part, &input, case.kind == Kind::Example)))
+        .map_err(|_| "panicked".to_string())?
+        .map_err(|err| err.in_file(&source).to_string())?
+        .answer;
+    if &answer == expected {
+        Ok(())
+    } else {
+        Err(format!("got {}, expected {}", answer, expected))
+    }
+}
+
+fn check(slow: bool) {
+    let registry = registry();
+    let failures: Vec<String> = cases().iter()
+        .filter(|case| case.slow == slow)
+        .filter_map(|case| {
+            let solution = registry.get(case.year, case.day).unwrap();
+            run_case(solution, case).err().map(|err| {
+                let kind = match case.kind { Kind::Input => "input", Kind::Example => "example" };
+                format!("{} day {} part {} ({}): {}", case.year, case.day, case.part, kind, err)
+            })
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n"));
+}
+
+#[test]
+fn golden_answers() {
+    check(false);
+}
+
+#[test]
+#[ignore = "runs the days marked slow in answers/"]
+fn slow_golden_answers() {
+    check(true);
+}
@@ -0,0 +1,397 @@
+use std::fmt::Display;
+use aoc::grid::{Direction, Grid, GridCursor, GridCursorMut, GridMut, HashMapGrid, Offset, Stitched, Torus, VecGrid, View};
+
+// What a grid under test should hold: the cells of `model`, a grid at the
+// origin, copied so that its top left is at (top, left).
+struct Fixture<T> {
+    model: VecGrid<T>,
+    top: i32,
+    left: i32,
+}
+
+impl<T: Clone + Default> Fixture<T> {
+    fn new(model: VecGrid<T>, top: i32, left: i32) -> Self {
+        Self { model, top, left }
+    }
+
+    // The value `down` rows and `right` columns from the top left.
+    fn at(&self, down: i32, right: i32) -> T {
+        self.model.get(down, right).clone()
+    }
+
+    fn bottom(&self) -> i32 { self.top + self.model.height() as i32 - 1 }
+    fn right(&self) -> i32 { self.left + self.model.width() as i32 - 1 }
+
+    fn map_grid(&self) -> HashMapGrid<T> {
+        let mut grid = HashMapGrid::new(T::default());
+        for c in self.model.cursors() {
+            grid.set(self.top + c.row, self.left + c.col, (*c).clone());
+        }
+        grid
+    }
+}
+
+// rows x cols cells, each holding row * 10 + col.
+fn numbers(rows: usize, cols: usize) -> VecGrid<i32> {
+    let cells : Vec<Vec<i32>> = (0..rows as i32)
+        .map(|row| (0..cols as i32).map(|col| row * 10 + col).collect())
+        .collect();
+    cells.into()
+}
+
+// Runs `check(grid, fixture)` on `model` and on a HashMapGrid copy of it
+// away from the origin. `mut check` hands the grids over mutably.
+macro_rules! on_both_grids {
+    ($model:expr, $check:ident) => { on_both_grids!(@run $model, $check, &) };
+    ($model:expr, mut $check:ident) => { on_both_grids!(@run $model, $check, &mut) };
+    (@run $model:expr, $check:ident, $($borrow:tt)+) => {{
+        #[allow(unused_mut)]
+        let mut grid = $model;
+        let fixture = Fixture::new(VecGrid::from_grid(&grid), 0, 0);
+        $check($($borrow)+ grid, &fixture);
+        let fixture = Fixture::new(fixture.model, -4, 7);
+        #[allow(unused_mut)]
+        let mut grid = fixture.map_grid();
+        $check($($borrow)+ grid, &fixture);
+    }};
+}
+
+fn values<'a, G: Grid + 'a>(cursors: impl Iterator<Item = GridCursor<'a, G>>) -> Vec<G::Item> where G::Item: Clone {
+    cursors.map(|c| (*c).clone()).collect()
+}
+
+// The behaviour every Grid should share.
+fn check_filled<G: Grid<Item = i32>>(grid: &G, f: &Fixture<i32>) {
+    let (top, left, bottom, right) = (f.top, f.left, f.bottom(), f.right());
+    let (rows, cols) = (f.model.height(), f.model.width());
+    assert_eq!((grid.min_row(), grid.max_row()), (top, bottom));
+    assert_eq!((grid.min_col(), grid.max_col()), (left, right));
+    assert_eq!((grid.height(), grid.width()), (rows, cols));
+    assert!(!grid.is_empty());
+
+    for (row, col) in [(top, left), (top, right), (bottom, left), (bottom, right)] {
+        assert!(grid.in_bounds(row, col), "({}, {}) should be in bounds", row, col);
+        assert_eq!(*grid.get(row, col), f.at(row - top, col - left));
+    }
+    for (row, col) in [(top - 1, left), (bottom + 1, left), (top, left - 1), (top, right + 1)] {
+        assert!(!grid.in_bounds(row, col), "({}, {}) should be out of bounds", row, col);
+        assert!(grid.cursor_at(row, col).is_none());
+    }
+
+    let cells : Vec<(i32, i32, i32)> = grid.cursors().map(|c| (c.row, c.col, *c)).collect();
+    let expected : Vec<(i32, i32, i32)> = (top..=bottom)
+        .flat_map(|row| (left..=right).map(move |col| (row, col)))
+        .map(|(row, col)| (row, col, f.at(row - top, col - left)))
+        .collect();
+    assert_eq!(cells, expected);
+
+    let corner = grid.cursor_at(bottom, right).unwrap();
+    assert!(corner.right().is_none());
+    assert!(corner.down().is_none());
+    assert_eq!(corner.iter_by(-1, -1).count(), rows.min(cols) - 1);
+}
+
+fn check_empty<G: Grid>(grid: &G) {
+    assert_eq!((grid.height(), grid.width()), (0, 0));
+    assert!(grid.is_empty());
+    assert!(!grid.in_bounds(0, 0));
+    assert!(grid.cursor_at(0, 0).is_none());
+    assert_eq!(grid.cursors().count(), 0);
+}
+
+#[test]
+fn grids_conform() {
+    on_both_grids!(numbers(3, 4), check_filled);
+    on_both_grids!(numbers(1, 1), check_filled);
+}
+
+#[test]
+fn vec_grid_new_and_set() {
+    let mut grid = VecGrid::new(3, 4, 0);
+    for c in numbers(3, 4).cursors() {
+        grid.set(c.row, c.col, *c);
+    }
+    assert_eq!(grid, numbers(3, 4));
+}
+
+#[test]
+fn empty_grids_conform() {
+    check_empty(&VecGrid::<i32>::default());
+    check_empty(&VecGrid::new(0, 0, 0));
+    check_empty(&VecGrid::<i32>::from_str_with("", |_| 0));
+    check_empty(&HashMapGrid::new(0));
+}
+
+#[test]
+fn vec_grid_from_str_with() {
+    let grid = VecGrid::from_str_with("12\n34\n56\n", |c| c.to_digit(10).unwrap());
+    assert_eq!((grid.height(), grid.width()), (3, 2));
+    assert_eq!(*grid.get(2, 1), 6);
+    assert_eq!(grid.to_string(), "12\n34\n56\n");
+}
+
+#[test]
+#[should_panic(expected = "different lengths")]
+fn vec_grid_from_str_with_rejects_ragged_rows() {
+    let _ = VecGrid::from_str_with("12\n3\n", |c| c);
+}
+
+#[test]
+#[should_panic(expected = "different lengths")]
+fn vec_grid_rejects_ragged_rows() {
+    let _ = VecGrid::from(vec![vec![1, 2], vec![3]]);
+}
+
+// On a 3 x 3 grid.
+fn check_neighbors<G: Grid<Item = i32>>(grid: &G, f: &Fixture<i32>) {
+    let centre = grid.cursor_at(f.top + 1, f.left + 1).unwrap();
+    assert_eq!(values(centre.neighbors4()), [f.at(0, 1), f.at(1, 2), f.at(2, 1), f.at(1, 0)]);
+    assert_eq!(values(centre.diagonals()), [f.at(0, 2), f.at(2, 2), f.at(2, 0), f.at(0, 0)]);
+    assert_eq!(centre.neighbors8().count(), 8);
+
+    let corner = grid.cursor_at(f.top, f.left).unwrap();
+    assert_eq!(values(corner.neighbors4()), [f.at(0, 1), f.at(1, 0)]);
+    assert_eq!(values(corner.neighbors8()), [f.at(0, 1), f.at(1, 1), f.at(1, 0)]);
+    assert_eq!(values(corner.neighbors_with(&[(2, 2), (0, -1), (1, 0)])), [f.at(2, 2), f.at(1, 0)]);
+    assert_eq!(corner.step(Direction::Down).map(|c| *c), Some(f.at(1, 0)));
+    assert_eq!(corner.step(Offset::DownRight).map(|c| *c), Some(f.at(1, 1)));
+    assert!(corner.step(Direction::Left).is_none());
+}
+
+#[test]
+fn neighbors_conform() {
+    on_both_grids!(numbers(3, 3), check_neighbors);
+}
+
+#[test]
+fn direction_turns() {
+    for dir in Direction::ALL {
+        assert_eq!(dir.turn_right().turn_left(), dir);
+        assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+        let (down, right) = dir.delta();
+        assert_eq!(dir.opposite().delta(), (-down, -right));
+        assert_eq!(dir.turn_right().delta(), (right, -down));
+        assert_eq!(Offset::from(dir).turn_right(), Offset::from(dir.turn_right()));
+    }
+    for offset in Offset::ALL {
+        let (down, right) = offset.delta();
+        assert_eq!(offset.turn_right().delta(), (right, -down));
+        assert_eq!(offset.turn_left().turn_right(), offset);
+        assert_eq!(offset.opposite().delta(), (-down, -right));
+    }
+    assert_eq!(Direction::Up.turn_left(), Direction::Left);
+}
+
+fn digit(c: char) -> Result<u32, String> {
+    c.to_digit(10).ok_or_else(|| format!("expected a digit, found {:?}", c))
+}
+
+#[test]
+fn parse_chars_reports_row_and_column() {
+    let grid = VecGrid::parse_chars("123\n456\n", digit).unwrap();
+    assert_eq!((grid.height(), grid.width()), (2, 3));
+    assert_eq!(*grid.get(1, 2), 6);
+
+    let err = VecGrid::parse_chars("123\n4x6\n", digit).unwrap_err();
+    assert_eq!((err.line, err.column), (2, 2));
+    assert_eq!(err.message, "expected a digit, found 'x'");
+
+    let err = VecGrid::parse_chars("123\n45\n", digit).unwrap_err();
+    assert_eq!((err.line, err.column), (2, 1));
+}
+
+#[test]
+fn parse_chars_with_markers_finds_landmarks() {
+    let (grid, markers) = VecGrid::parse_chars_with_markers("S.#\n..E\n#S.\n", &['S', 'E', '@'], |c| {
+        Ok::<_, String>(c == '#')
+    }).unwrap();
+    assert!(*grid.get(0, 2) && *grid.get(2, 0) && !*grid.get(0, 0));
+    assert_eq!(markers[&'S'], [(0, 0), (2, 1)]);
+    assert_eq!(markers[&'E'], [(1, 2)]);
+    assert!(markers[&'@'].is_empty());
+}
+
+fn chars(text: &str) -> VecGrid<char> {
+    VecGrid::from_str_with(text, |c| c)
+}
+
+// How `grid` looks, wherever it is.
+fn show<G: Grid>(grid: &G) -> String where G::Item: Display {
+    View::new(grid).to_string()
+}
+
+// On "123\n456\n".
+fn check_views<G: Grid<Item = char>>(grid: &G, f: &Fixture<char>) {
+    assert_eq!(grid.transposed().to_string(), "14\n25\n36\n");
+    assert_eq!(grid.rotated_cw().to_string(), "41\n52\n63\n");
+    assert_eq!(grid.flipped_h().to_string(), "321\n654\n");
+    assert_eq!(grid.rotated_cw().rotated_cw().to_string(), "654\n321\n");
+    assert_eq!(grid.rotated_cw().rotated_cw().rotated_cw().rotated_cw().to_string(), show(grid));
+    assert_eq!(grid.transposed().flipped_h().to_string(), grid.rotated_cw().to_string());
+
+    let window = grid.window(f.top, f.left + 1, f.bottom() + 5, f.right());
+    assert_eq!(window.to_string(), "23\n56\n");
+    assert_eq!((window.min_row(), window.min_col()), (f.top, f.left + 1));
+    assert_eq!(window.rotated_cw().to_string(), "52\n63\n");
+    assert!(grid.window(f.bottom() + 1, 0, f.bottom() + 2, 0).is_empty());
+}
+
+#[test]
+fn views_conform() {
+    on_both_grids!(chars("123\n456\n"), check_views);
+}
+
+#[test]
+fn window_keeps_coordinates() {
+    let grid = numbers(5, 5);
+    let expected = Fixture::new(VecGrid::from(vec![vec![12, 13], vec![22, 23], vec![32, 33]]), 1, 2);
+    check_filled(&grid.window(1, 2, 3, 3), &expected);
+}
+
+#[test]
+fn views_write_through() {
+    let mut grid = chars("123\n456\n");
+    View::new(&mut grid).rotated_cw().set(0, 0, '0');
+    View::new(&mut grid).flipped_h().window(1, 0, 1, 0).set(1, 0, '9');
+    assert_eq!(grid.to_string(), "123\n059\n");
+}
+
+#[test]
+fn vec_grid_owned_transforms() {
+    let mut grid = chars("123\n456\n");
+    grid.rotate_cw();
+    assert_eq!(grid.to_string(), "41\n52\n63\n");
+    assert_eq!((grid.height(), grid.width()), (3, 2));
+    grid.transpose();
+    assert_eq!(grid.to_string(), "456\n123\n");
+    grid.flip_h();
+    assert_eq!(grid.to_string(), "654\n321\n");
+    grid.crop(0, 1, 1, 2);
+    assert_eq!(grid.to_string(), "54\n21\n");
+    assert_eq!((grid.min_row(), grid.min_col()), (0, 0));
+}
+
+// On a 3 x 4 grid.
+fn check_lines<G: Grid<Item = i32>>(grid: &G, f: &Fixture<i32>) {
+    let (top, left) = (f.top, f.left);
+    assert_eq!(values(grid.row(top + 1)), [f.at(1, 0), f.at(1, 1), f.at(1, 2), f.at(1, 3)]);
+    assert_eq!(values(grid.col(left + 3)), [f.at(0, 3), f.at(1, 3), f.at(2, 3)]);
+    assert_eq!(grid.rows().map(|row| row.count()).collect::<Vec<_>>(), [4, 4, 4]);
+    assert_eq!(grid.cols().map(|col| col.count()).collect::<Vec<_>>(), [3, 3, 3, 3]);
+    let rows : Vec<i32> = grid.rows().flat_map(values).collect();
+    assert_eq!(rows, values(grid.cursors()));
+
+    assert_eq!(values(grid.ray((top, left), Offset::DownRight)), [f.at(1, 1), f.at(2, 2)]);
+    assert_eq!(values(grid.ray((top + 2, left + 1), Direction::Up)), [f.at(1, 1), f.at(0, 1)]);
+    // A ray can start just outside the grid, looking in.
+    assert_eq!(values(grid.ray((top + 1, left + 4), Direction::Left)), [f.at(1, 3), f.at(1, 2), f.at(1, 1), f.at(1, 0)]);
+    assert_eq!(grid.ray((top, left), Direction::Left).count(), 0);
+}
+
+#[test]
+fn lines_conform() {
+    on_both_grids!(numbers(3, 4), check_lines);
+    check_lines(&View::new(&numbers(3, 4)), &Fixture::new(numbers(3, 4), 0, 0));
+    assert_eq!(VecGrid::<i32>::default().rows().count(), 0);
+}
+
+// On "9..\n...\n": walks the 9 down and right until it's stopped, leaving
+// a 1 behind it.
+fn check_cursor_mut<G: GridMut<Item = char>>(grid: &mut G, f: &Fixture<char>) {
+    let (top, left) = (f.top, f.left);
+    let mut cursor : GridCursorMut<G> = grid.cursor_at_mut(top, left).unwrap();
+    assert_eq!(*cursor, '9');
+    assert_eq!(cursor.neighbor(Direction::Up), None);
+    assert_eq!(cursor.as_cursor().neighbors4().count(), 2);
+    while cursor.neighbor(Offset::DownRight) == Some(&'.') {
+        assert!(cursor.swap_with(Offset::DownRight));
+        *cursor = '1';
+        assert!(cursor.step(Offset::DownRight));
+    }
+    assert!(!cursor.swap_with(Direction::Down));
+    assert!(!cursor.move_by(1, 0));
+    assert_eq!((cursor.row, cursor.col), (top + 1, left + 1));
+    *grid.get_mut(top + 1, left + 2) = '2';
+    grid.swap((top, left + 1), (top + 1, left + 2));
+    assert_eq!(show(grid), "12.\n.9.\n");
+}
+
+#[test]
+fn cursors_write_through() {
+    on_both_grids!(chars("9..\n...\n"), mut check_cursor_mut);
+}
+
+#[test]
+fn torus_wraps_around() {
+    let grid = numbers(3, 4);
+    let corner = grid.cursor_at(0, 3).unwrap();
+    let (next, dir) = corner.walk(&Torus, Direction::Right).unwrap();
+    assert_eq!((next.row, next.col, dir), (0, 0, Direction::Right));
+    let (next, _) = corner.walk(&Torus, Direction::Up).unwrap();
+    assert_eq!((next.row, next.col), (2, 3));
+
+    // Wrapping inside a wall.
+    let inside = grid.window(1, 1, 1, 2);
+    let (next, _) = inside.cursor_at(1, 2).unwrap().walk(&Torus, Direction::Right).unwrap();
+    assert_eq!((next.row, next.col, *next), (1, 1, 11));
+}
+
+#[test]
+fn stitches_work_both_ways() {
+    let grid = VecGrid::new(3, 3, 0);
+    let mut stitched = Stitched::new();
+    stitched.stitch((0, 2), Direction::Right, (2, 0), Direction::Up);
+    let cursor = grid.cursor_at(0, 2).unwrap();
+    let (next, dir) = cursor.walk(&stitched, Direction::Right).unwrap();
+    assert_eq!((next.row, next.col, dir), (2, 0, Direction::Up));
+    let (back, dir) = next.walk(&stitched, Direction::Down).unwrap();
+    assert_eq!((back.row, back.col, dir), (0, 2, Direction::Left));
+    assert!(cursor.walk(&stitched, Direction::Up).is_none());
+    assert_eq!(cursor.walk(&stitched, Direction::Left).map(|(c, _)| c.col), Some(1));
+}
+
+// The example from 2022 day 22, with faces of 4 x 4.
+const CUBE_NET : &str = "        ...#    \n        .#..    \n        #...    \n        ....    \n...#.......#    \n........#...    \n..#....#....    \n..........#.    \n        ...#....\n        .....#..\n        .#......\n        ......#.\n";
+
+#[test]
+fn cube_net_folds() {
+    let mut grid = chars(CUBE_NET);
+    let cube = Stitched::fold_cube(&grid, |c| *c != ' ').unwrap();
+
+    // The turns the puzzle walks through.
+    let mut cursor = grid.cursor_at_mut(5, 11).unwrap();
+    assert_eq!(cursor.walk(&cube, Direction::Right), Some(Direction::Down));
+    assert_eq!((cursor.row, cursor.col), (8, 14));
+    let mut cursor = grid.cursor_at_mut(11, 10).unwrap();
+    assert_eq!(cursor.walk(&cube, Direction::Down), Some(Direction::Up));
+    assert_eq!((cursor.row, cursor.col), (7, 1));
+    let mut cursor = grid.cursor_at_mut(4, 6).unwrap();
+    assert_eq!(cursor.walk(&cube, Direction::Up), Some(Direction::Right));
+    assert_eq!((cursor.row, cursor.col), (2, 8));
+
+    // Going straight on goes round the cube and back; turning round
+    // retraces the steps.
+    for start in grid.cursors().filter(|c| **c != ' ') {
+        for dir in Direction::ALL {
+            let (mut cursor, mut heading) = (start.clone(), dir);
+            for _ in 0..16 {
+                let (next, next_heading) = cursor.walk(&cube, heading).unwrap();
+                assert_ne!(*next, ' ');
+                let (back, back_heading) = next.walk(&cube, next_heading.opposite()).unwrap();
+                assert_eq!((back.row, back.col, back_heading), (cursor.row, cursor.col, heading.opposite()));
+                (cursor, heading) = (next, next_heading);
+            }
+            assert_eq!((cursor.row, cursor.col, heading), (start.row, start.col, dir));
+        }
+    }
+}
+
+#[test]
+fn cube_net_errors() {
+    let grid = chars("..\n..\n");
+    assert!(Stitched::fold_cube(&grid, |c| *c == '.').is_err());
+    let grid = chars("...... \n");
+    assert_eq!(Stitched::fold_cube(&grid, |c| *c == '.').unwrap_err(), "faces overlap when folded");
+    let grid = chars("...\n   \n...\n");
+    assert_eq!(Stitched::fold_cube(&grid, |c| *c == '.').unwrap_err(), "the faces aren't joined up");
+}